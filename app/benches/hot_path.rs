@@ -0,0 +1,252 @@
+//! The per-block hot path over synthetic trove books: event decoding, trove upserts, cache
+//! reloads and the liquidation scan. Oracle reads are answered by a `ReplayTransport`, so no node
+//! is needed. Run with `cargo bench -p app`.
+
+use std::{hint::black_box, path::PathBuf, sync::Arc};
+
+use alloy::{
+    network::EthereumWallet,
+    primitives::{Address, B256, I256, U256, address},
+    providers::ProviderBuilder,
+    rpc::{client::ClientBuilder, types::Log},
+    signers::local::PrivateKeySigner,
+    sol_types::SolEvent,
+};
+use app::{
+    DefaultProvider,
+    db::{DatabaseStore, initialize_database, store::Trove},
+    liquity::{
+        liquidation_recorder::LiquidationRecorder,
+        liquity::{TroveManager, decode_event_log},
+        liquity_exexcution::LiquityExecutor,
+        liquity_strategy::{LiquityStrategy, StrategyProvider, scan_troves},
+        trove_memory_cache::TroveMemoryCache,
+    },
+    replay::{ReplayTransport, RpcExchange},
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+const TROVE_MANAGER: Address = address!("0x0000000000000000000000000000000000000a11");
+const ORACLE: Address = address!("0x0000000000000000000000000000000000000b22");
+const LIQUIDATOR: Address = address!("0x0000000000000000000000000000000000000c33");
+/// Well-known anvil development key; never holds real funds
+const TEST_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcaf784d7bf4f2ff80";
+
+const E18: u128 = 1_000_000_000_000_000_000;
+const BOOK_SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const UPSERT_BATCH: usize = 1_000;
+/// Last debt update of every synthetic trove
+const UPDATED_AT: u64 = 1_700_000_000;
+/// Ten minutes after the last update, well before any liquidation price expires
+const BLOCK_TIMESTAMP: u64 = UPDATED_AT + 600;
+/// 2,000 USD with 8 decimals, as `latestAnswer()` returns it
+const ORACLE_ANSWER: u64 = 200_000_000_000;
+
+fn mcr() -> U256 {
+    U256::from(11 * E18 / 10)
+}
+
+/// Trove `index` of a healthy book: at least 150% ICR at 2,000, with rates from 0.5% to 20%
+fn synthetic_trove(index: usize) -> Trove {
+    let coll = U256::from((5 + index % 20) as u128 * E18);
+    let debt = U256::from((5_000 + (index * 7_919) % 15_000) as u128 * E18);
+    let debt = debt.min(coll * U256::from(2_000u64 * 100 / 150));
+    let rate = U256::from((5 + (index * 31) % 196) as u128 * E18 / 1_000);
+    let liquidation_price =
+        LiquityStrategy::calculate_liquidation_price(debt, coll, rate, mcr(), 0);
+
+    Trove {
+        trove_id: (index + 1).to_string(),
+        collateral: coll.into(),
+        debt: debt.into(),
+        icr_key: Trove::icr_key(coll, debt),
+        interest_rate: rate.into(),
+        status: "active".to_string(),
+        last_updated: 20_000_000,
+        last_updated_at: UPDATED_AT as i64,
+        liquidation_price: liquidation_price.into(),
+        liquidation_price_until: (UPDATED_AT + 10 * 31_536_000) as i64,
+    }
+}
+
+fn temp_db(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("liquidator-bench-{}-{}.db", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn store(name: &str) -> Arc<dyn DatabaseStore> {
+    let path = temp_db(name);
+    initialize_database(&format!("sqlite:{}", path.display())).await.unwrap()
+}
+
+/// A store holding `size` open troves
+async fn book(size: usize) -> Arc<dyn DatabaseStore> {
+    let store = store(&format!("book-{}", size)).await;
+    for index in 0..size {
+        store.upsert_trove(&synthetic_trove(index)).await.unwrap();
+    }
+    store
+}
+
+fn log(event: impl SolEvent, index: u64) -> Log {
+    Log {
+        inner: alloy::primitives::Log { address: TROVE_MANAGER, data: event.encode_log_data() },
+        block_number: Some(20_000_000),
+        block_timestamp: Some(UPDATED_AT),
+        transaction_hash: Some(B256::repeat_byte(0x11)),
+        log_index: Some(index),
+        ..Default::default()
+    }
+}
+
+/// ABI encoding of a single static word
+fn word(value: U256) -> String {
+    format!("0x{:x}", B256::from(value))
+}
+
+/// A strategy over `store` whose oracle reads are served from memory
+async fn strategy(store: Arc<dyn DatabaseStore>) -> LiquityStrategy {
+    let replay = ReplayTransport::new(vec![
+        // latestAnswer() and latestTimestamp()
+        RpcExchange::ok("eth_call", word(U256::from(ORACLE_ANSWER)))
+            .with_params(json!([{ "to": ORACLE.to_string(), "input": "0x50d25bcd" }])),
+        RpcExchange::ok("eth_call", word(U256::from(BLOCK_TIMESTAMP)))
+            .with_params(json!([{ "to": ORACLE.to_string(), "input": "0x8205bf6a" }])),
+    ]);
+    let client = || ClientBuilder::default().transport(replay.clone(), true);
+    let provider: Arc<StrategyProvider> =
+        Arc::new(ProviderBuilder::new().connect_client(client()));
+    let signer: PrivateKeySigner = TEST_KEY.parse().unwrap();
+    let wallet_provider: Arc<DefaultProvider> = Arc::new(
+        ProviderBuilder::new().wallet(EthereumWallet::from(signer)).connect_client(client()),
+    );
+
+    let executor =
+        LiquityExecutor::new(LIQUIDATOR, TROVE_MANAGER, wallet_provider, provider.clone());
+    let recorder =
+        LiquidationRecorder::new(TROVE_MANAGER, LIQUIDATOR, None, store.clone(), provider.clone());
+    LiquityStrategy::new(TROVE_MANAGER, store, provider, ORACLE, mcr(), executor, recorder).await
+}
+
+fn event_decoding(c: &mut Criterion) {
+    let trove_updated = log(
+        TroveManager::TroveUpdated {
+            _troveId: U256::from(42),
+            _debt: U256::from(20_000 * E18),
+            _coll: U256::from(10 * E18),
+            _annualInterestRate: U256::from(5 * E18 / 100),
+            ..Default::default()
+        },
+        0,
+    );
+    let trove_operation = log(
+        TroveManager::TroveOperation {
+            _troveId: U256::from(42),
+            _annualInterestRate: U256::from(5 * E18 / 100),
+            _debtChangeFromOperation: I256::try_from(1_000 * E18).unwrap(),
+            ..Default::default()
+        },
+        1,
+    );
+    let liquidation = log(
+        TroveManager::Liquidation {
+            _debtOffsetBySP: U256::from(20_000 * E18),
+            _collGasCompensation: U256::from(5 * E18 / 100),
+            _collSentToSP: U256::from(995 * E18 / 100),
+            _price: U256::from(1_900 * E18),
+            ..Default::default()
+        },
+        2,
+    );
+
+    let mut group = c.benchmark_group("decode_event_log");
+    let logs = [
+        ("TroveUpdated", trove_updated),
+        ("TroveOperation", trove_operation),
+        ("Liquidation", liquidation),
+    ];
+    for (name, log) in logs {
+        group.bench_function(name, |b| b.iter(|| decode_event_log(black_box(&log))));
+    }
+    group.finish();
+}
+
+fn trove_upserts(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let store = runtime.block_on(store("upserts"));
+    let troves: Vec<Trove> = (0..UPSERT_BATCH).map(synthetic_trove).collect();
+
+    let mut group = c.benchmark_group("upsert_trove");
+    group.throughput(Throughput::Elements(UPSERT_BATCH as u64));
+    group.sample_size(10);
+    group.bench_function("store", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for trove in &troves {
+                    store.upsert_trove(trove).await.unwrap();
+                }
+            })
+        })
+    });
+    group.bench_function("memory_cache", |b| {
+        let cache = TroveMemoryCache::new();
+        b.iter(|| {
+            runtime.block_on(async {
+                for trove in &troves {
+                    cache.upsert(&store, trove).await.unwrap();
+                }
+            })
+        })
+    });
+    group.finish();
+}
+
+fn cache_reload(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("cache_reload");
+    group.sample_size(10);
+    for size in BOOK_SIZES {
+        let store = runtime.block_on(book(size));
+        let cache = TroveMemoryCache::new();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| runtime.block_on(cache.load(&store)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn liquidation_scan(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let price = U256::from(ORACLE_ANSWER) * U256::from(10_000_000_000u64);
+
+    let mut group = c.benchmark_group("liquidation_scan");
+    group.sample_size(10);
+    for size in BOOK_SIZES {
+        let store = runtime.block_on(book(size));
+        let troves = runtime.block_on(store.get_open_troves()).unwrap();
+        group.throughput(Throughput::Elements(size as u64));
+
+        // Every trove checked, as for price-index candidates and in backtests
+        group.bench_with_input(BenchmarkId::new("scan_troves", size), &troves, |b, troves| {
+            b.iter(|| scan_troves(black_box(troves), BLOCK_TIMESTAMP, price, mcr(), false))
+        });
+
+        // A block's full check; the first call loads the book and runs the projection pass, so
+        // it happens before measuring
+        let strategy = runtime.block_on(strategy(store));
+        let check = || strategy.check_for_liquidation_opportunities(20_000_001, BLOCK_TIMESTAMP);
+        assert!(runtime.block_on(check()).unwrap().is_empty());
+        group.bench_function(BenchmarkId::new("check_for_liquidation_opportunities", size), |b| {
+            b.iter(|| runtime.block_on(check()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, event_decoding, trove_upserts, cache_reload, liquidation_scan);
+criterion_main!(benches);
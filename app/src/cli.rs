@@ -0,0 +1,358 @@
+use std::{collections::HashSet, path::PathBuf, str::FromStr};
+
+use alloy::{primitives::U256, providers::Provider};
+use clap::{Args, Parser, Subcommand};
+use eyre::Result;
+
+use app::{
+    config::{CandidateSource, ProtocolConfig},
+    db::store::{Trove, TroveStatus},
+    liquity::{
+        backtest::{BacktestConfig, Backtester, SimulatedExecutor, load_records},
+        liquidation_recorder::units_to_f64,
+        liquity::TroveManager,
+        liquity_strategy::{LiquityStrategy, block_timestamp_of},
+        reconciler::TroveReconciler,
+        sorted_troves::SortedTrovesMirror,
+    },
+};
+
+use crate::App;
+
+#[derive(Debug, Parser)]
+#[command(name = "liquidator", about = "Liquity v2 liquidation bot")]
+pub struct Cli {
+    /// Protocol to work on (`felix`, `liquity`)
+    pub protocol: String,
+
+    /// Simulate and record liquidations instead of sending them
+    #[arg(long, global = true)]
+    pub shadow: bool,
+
+    /// Defaults to `run`
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Backfill history, then follow new blocks and liquidate
+    Run,
+    /// Ingest TroveManager logs for a block range without checking for liquidations
+    Backfill {
+        /// First block to ingest; defaults to the last ingested block
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last block to ingest; defaults to the chain head
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// Rebuild every open trove from `TroveManager` and `SortedTroves` state
+    Resync,
+    /// Print the riskiest open troves with their live ICR
+    Troves {
+        #[arg(long, default_value_t = 20)]
+        lowest: usize,
+    },
+    /// Show a trove's stored state, event history and computed vs. on-chain ICR
+    Trove { id: String },
+    /// Liquidate the given troves once through the executor
+    Liquidate {
+        #[arg(required = true)]
+        ids: Vec<String>,
+        /// Simulate the transaction and report its gas instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Compare shadow-mode decisions with the troves' actual on-chain liquidations
+    ShadowReport {
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+    /// Replay recorded TroveManager logs and oracle prices through the strategy, offline
+    Backtest(BacktestArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BacktestArgs {
+    /// JSON-lines file of `{"type":"log",...}`, `{"type":"price",...}` and
+    /// `{"type":"native_price",...}` records
+    pub file: PathBuf,
+    /// Candidate source to replay; defaults to the protocol's configured one
+    #[arg(long, value_enum)]
+    pub source: Option<CandidateSource>,
+    /// Minimum collateral ratio (18 decimals)
+    #[arg(long, default_value = "1100000000000000000")]
+    pub mcr: U256,
+    /// Gas price (wei) used for modeled costs
+    #[arg(long, default_value_t = 1_000_000_000)]
+    pub gas_price: u128,
+    #[arg(long, default_value_t = 100_000)]
+    pub gas_per_trove: u64,
+    #[arg(long, default_value_t = 150_000)]
+    pub gas_per_batch: u64,
+}
+
+fn parse_trove_id(id: &str) -> Result<U256> {
+    U256::from_str(id).map_err(|e| eyre::eyre!("Invalid trove id {}: {}", id, e))
+}
+
+fn format_icr(icr: Option<U256>) -> String {
+    match icr {
+        Some(icr) => format!("{:.2}%", units_to_f64(&icr.to_string()) * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+/// ICR of a stored trove at `block_timestamp`. Rows stored before update timestamps were
+/// tracked would accrue interest from the epoch, so their ICR is unknown rather than wrong.
+fn stored_icr(trove: &Trove, block_timestamp: u64, price: U256) -> Option<U256> {
+    if trove.last_updated_at == 0 {
+        return None;
+    }
+    LiquityStrategy::trove_icr(trove, block_timestamp, price)
+}
+
+fn format_stored_icr(trove: &Trove, icr: Option<U256>) -> String {
+    if trove.last_updated_at == 0 {
+        return "unknown".to_string();
+    }
+    format_icr(icr)
+}
+
+pub async fn backfill(app: &App, from: Option<u64>, to: Option<u64>) -> Result<()> {
+    let last_block = app.last_block().await?;
+    let from = from.unwrap_or(last_block);
+    let to = match to {
+        Some(to) => to,
+        None => app.provider.get_block_number().await?,
+    };
+    if from > to {
+        return Err(eyre::eyre!("--from {} is after --to {}", from, to));
+    }
+
+    let strategy = app.strategy().await;
+    let mut log_collector = app.log_collector(&strategy, from).await;
+    log_collector.set_end_block(to);
+    log_collector.start_listening_with_history().await?;
+
+    // Re-ingesting an old range must not rewind where `run` resumes from
+    app.store.set_last_block(last_block.max(to) as i64).await?;
+    println!("✅ Backfilled blocks {} to {}", from, to);
+    Ok(())
+}
+
+pub async fn resync(app: &App) -> Result<()> {
+    let mirror = SortedTrovesMirror::new(app.sorted_troves, app.provider.clone());
+    mirror.seed().await?;
+    let on_chain_ids = mirror.candidates(mirror.size().await).await;
+
+    // Stored open troves cover zombies, which are not in SortedTroves, and closed-on-chain ones
+    let mut troves = app.store.get_open_troves().await?;
+    let known: HashSet<String> = troves.iter().map(|trove| trove.trove_id.clone()).collect();
+    for trove_id in on_chain_ids {
+        let trove_id = trove_id.to_string();
+        if known.contains(&trove_id) {
+            continue;
+        }
+        if let Some(trove) = app.store.get_trove_by_id(&trove_id).await? {
+            troves.push(trove);
+            continue;
+        }
+        // Unknown locally; the reconciler fills it in from chain state
+        troves.push(Trove {
+            trove_id,
+            collateral: U256::ZERO.into(),
+            debt: U256::ZERO.into(),
+            icr_key: Trove::icr_key(U256::ZERO, U256::ZERO),
+            interest_rate: U256::ZERO.into(),
+            status: TroveStatus::NonExistent.as_str().to_string(),
+            last_updated: 0,
+            last_updated_at: 0,
+            liquidation_price: U256::ZERO.into(),
+            liquidation_price_until: 0,
+        });
+    }
+
+    println!("🔁 Resyncing {} troves from chain state", troves.len());
+    let reconciler = TroveReconciler::new(
+        app.trove_manager,
+        app.store.clone(),
+        app.provider.clone(),
+        app.config.reconcile_interval_secs,
+        app.config.reconcile_batch_size,
+    );
+    let summary = reconciler.reconcile_troves(&troves).await?;
+    TroveReconciler::report(&summary);
+    println!(
+        "✅ Resync checked {} troves, repaired {} ({} errors)",
+        summary.checked, summary.repaired, summary.errors
+    );
+    Ok(())
+}
+
+pub async fn troves(app: &App, lowest: usize) -> Result<()> {
+    let strategy = app.strategy().await;
+    let price = strategy.get_oracle_price().await?;
+    let block_number = app.provider.get_block_number().await?;
+    let block_timestamp = block_timestamp_of(&app.provider, block_number).await?;
+
+    let mut rows: Vec<(Trove, Option<U256>)> = app
+        .store
+        .get_open_troves()
+        .await?
+        .into_iter()
+        .map(|trove| {
+            let icr = stored_icr(&trove, block_timestamp, price);
+            (trove, icr)
+        })
+        .collect();
+    // Troves without a computable ICR sort last
+    rows.sort_by_key(|(_, icr)| icr.unwrap_or(U256::MAX));
+    rows.truncate(lowest);
+
+    println!(
+        "Block #{} - price {:.4} - MCR {}",
+        block_number,
+        units_to_f64(&price.to_string()),
+        format_icr(Some(app.mcr))
+    );
+    println!(
+        "{:<78} {:>8} {:>14} {:>14} {:>8} {:>12} {:>10}",
+        "trove", "status", "collateral", "debt", "rate", "liq. price", "live ICR"
+    );
+    for (trove, icr) in rows {
+        let marker = if icr.is_some_and(|icr| icr < app.mcr) { " ⚠️" } else { "" };
+        println!(
+            "{:<78} {:>8} {:>14.4} {:>14.2} {:>7.2}% {:>12.4} {:>10}{}",
+            trove.trove_id,
+            trove.status,
+            units_to_f64(&trove.collateral.to_string()),
+            units_to_f64(&trove.debt.to_string()),
+            units_to_f64(&trove.interest_rate.to_string()) * 100.0,
+            units_to_f64(&trove.liquidation_price.to_string()),
+            format_stored_icr(&trove, icr),
+            marker
+        );
+    }
+    Ok(())
+}
+
+pub async fn trove(app: &App, id: &str) -> Result<()> {
+    let trove_id = parse_trove_id(id)?;
+    let trove_id_str = trove_id.to_string();
+
+    let strategy = app.strategy().await;
+    let price = strategy.get_oracle_price().await?;
+    let block_number = app.provider.get_block_number().await?;
+    let block_timestamp = block_timestamp_of(&app.provider, block_number).await?;
+
+    match app.store.get_trove_by_id(&trove_id_str).await? {
+        Some(trove) => {
+            println!("Trove {}", trove.trove_id);
+            println!("  status:            {}", trove.status);
+            println!("  collateral:        {}", trove.collateral);
+            println!("  debt:              {}", trove.debt);
+            println!("  interest rate:     {}", trove.interest_rate);
+            println!(
+                "  last updated:      block {} ({})",
+                trove.last_updated, trove.last_updated_at
+            );
+            println!(
+                "  liquidation price: {} (until {})",
+                trove.liquidation_price, trove.liquidation_price_until
+            );
+            println!(
+                "  computed ICR:      {}",
+                format_stored_icr(&trove, stored_icr(&trove, block_timestamp, price))
+            );
+        }
+        None => println!("Trove {} is not stored locally", trove_id_str),
+    }
+
+    let trove_manager = TroveManager::new(app.trove_manager, &*app.provider);
+    let on_chain = trove_manager.Troves(trove_id).call().await?;
+    let on_chain_icr = trove_manager.getCurrentICR(trove_id, price).call().await?;
+    let on_chain_status = TroveStatus::from_chain(on_chain.status);
+    println!("  on-chain status:   {}", on_chain_status.as_str());
+    println!("  on-chain ICR:      {}", format_icr(Some(on_chain_icr)));
+    println!(
+        "  (block #{}, price {:.4}, MCR {})",
+        block_number,
+        units_to_f64(&price.to_string()),
+        format_icr(Some(app.mcr))
+    );
+
+    let timeline = app.store.get_trove_timeline(&trove_id_str).await?;
+    println!("History ({} events):", timeline.len());
+    for event in timeline {
+        println!(
+            "  #{:<10} {:<24} rate {:<20} debt Δ {:<24} coll Δ {:<24} {}",
+            event.block_number,
+            event.operation,
+            event.annual_interest_rate,
+            event.debt_change_from_operation,
+            event.coll_change_from_operation,
+            event.tx_hash
+        );
+    }
+    Ok(())
+}
+
+pub async fn liquidate(app: &App, ids: &[String], dry_run: bool) -> Result<()> {
+    let trove_ids = ids.iter().map(|id| parse_trove_id(id)).collect::<Result<Vec<_>>>()?;
+
+    let gas = app.executor.simulate(trove_ids.clone()).await?;
+    println!("🧪 Liquidating {} troves simulates fine - estimated gas {}", trove_ids.len(), gas);
+    if dry_run {
+        return Ok(());
+    }
+
+    let block_number = app.provider.get_block_number().await?;
+    match app.executor.execute(trove_ids, block_number).await? {
+        Some(tx_hash) => println!("✅ Liquidation sent: {:?}", tx_hash),
+        None => println!("🕶️ Shadow mode - liquidation recorded, not sent"),
+    }
+    Ok(())
+}
+
+pub async fn shadow_report(app: &App, limit: i64) -> Result<()> {
+    let rows = app.store.get_shadow_comparison(limit).await?;
+    println!(
+        "{:<78} {:>10} {:>9} {:>12} {:>10} {:>8}  liquidated by",
+        "trove", "shadow", "simulated", "est. gas", "actual", "delta"
+    );
+    for row in rows {
+        let optional = |value: Option<i64>| value.map_or("-".to_string(), |v| v.to_string());
+        println!(
+            "{:<78} {:>10} {:>9} {:>12} {:>10} {:>8}  {}",
+            row.trove_id,
+            row.shadow_block,
+            if row.simulated_ok { "ok" } else { "reverted" },
+            optional(row.estimated_gas),
+            optional(row.actual_block),
+            optional(row.blocks_after_shadow),
+            row.actual_sender.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+/// Runs without RPC or database access
+pub fn backtest(config: &ProtocolConfig, args: &BacktestArgs) -> Result<()> {
+    let records = load_records(&args.file)?;
+    println!("🧪 Backtesting {} records from {}", records.len(), args.file.display());
+
+    let backtester = Backtester::new(BacktestConfig {
+        mcr: args.mcr,
+        candidate_source: args.source.unwrap_or(config.candidate_source),
+        liquidation_price_horizon_secs: config.liquidation_price_horizon_secs,
+        executor: SimulatedExecutor {
+            gas_per_batch: args.gas_per_batch,
+            gas_per_trove: args.gas_per_trove,
+            gas_price: args.gas_price,
+        },
+    });
+    backtester.run(&records)?.print();
+    Ok(())
+}
@@ -1,24 +1,44 @@
 use alloy::providers::{IpcConnect, Provider, ProviderBuilder, WsConnect};
 use eyre::Result;
 use log::{error, info};
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::{
-    sync::RwLock,
+    sync::{Mutex, RwLock},
     time::{Instant, sleep},
 };
 
-use crate::strategy::Strategy;
+use crate::{
+    health, metrics,
+    notifier::{Alert, AlertKind, Notifier},
+    strategy::Strategy,
+};
 
 /// Block collector that listens to new blocks and triggers registered strategies
 pub struct BlockCollector {
     strategies: Arc<RwLock<Vec<Box<dyn Strategy<u64>>>>>,
     provider: Option<Arc<dyn Provider>>,
+    notifier: Notifier,
+    /// Consecutive failures per strategy name
+    error_streaks: Mutex<HashMap<String, u32>>,
+    error_streak_threshold: u32,
 }
 
 impl BlockCollector {
     /// Create a new block collector
     pub fn new() -> Self {
-        Self { strategies: Arc::new(RwLock::new(Vec::new())), provider: None }
+        Self {
+            strategies: Arc::new(RwLock::new(Vec::new())),
+            provider: None,
+            notifier: Notifier::disabled(),
+            error_streaks: Mutex::new(HashMap::new()),
+            error_streak_threshold: u32::MAX,
+        }
+    }
+
+    /// Alert once a strategy has failed `error_streak_threshold` blocks in a row
+    pub fn set_notifier(&mut self, notifier: Notifier, error_streak_threshold: u32) {
+        self.notifier = notifier;
+        self.error_streak_threshold = error_streak_threshold.max(1);
     }
 
     /// Add a strategy to the collector
@@ -87,8 +107,7 @@ impl BlockCollector {
 
         loop {
             let start_time = Instant::now();
-            let latest_block = provider.get_block_number().await?;
-            self.execute_strategies(&latest_block).await;
+            self.poll_once(provider).await?;
 
             // block_number += 1;
 
@@ -125,6 +144,15 @@ impl BlockCollector {
         // Ok(())
     }
 
+    /// Run every strategy once against the current head block
+    pub async fn poll_once(&self, provider: &Arc<dyn Provider>) -> Result<u64> {
+        let latest_block = provider.get_block_number().await?;
+        metrics::set_head_block(latest_block);
+        self.execute_strategies(&latest_block).await;
+        health::heartbeat();
+        Ok(latest_block)
+    }
+
     /// Execute all registered strategies for a given block
     async fn execute_strategies(&self, block_number: &u64) {
         let strategies = self.strategies.read().await;
@@ -135,7 +163,20 @@ impl BlockCollector {
         // Execute regular strategies
         for strategy in strategies.iter() {
             let strategy_name = strategy.name();
-            match strategy.execute(block_number).await {
+            let strategy_start = Instant::now();
+            let result = strategy.execute(block_number).await;
+            metrics::STRATEGY_DURATION
+                .with_label_values(&[strategy_name])
+                .observe(strategy_start.elapsed().as_secs_f64());
+
+            let streak = {
+                let mut streaks = self.error_streaks.lock().await;
+                let streak = streaks.entry(strategy_name.to_string()).or_default();
+                *streak = if result.is_ok() { 0 } else { *streak + 1 };
+                *streak
+            };
+
+            match result {
                 Ok(()) => {
                     info!(
                         "✅ Strategy '{}' executed successfully for block #{}",
@@ -143,10 +184,25 @@ impl BlockCollector {
                     );
                 }
                 Err(e) => {
+                    metrics::STRATEGY_ERRORS.with_label_values(&[strategy_name]).inc();
                     error!(
                         "❌ Strategy '{}' failed for block #{}: {}",
                         strategy_name, block_number, e
                     );
+                    if streak >= self.error_streak_threshold {
+                        self.notifier
+                            .notify(
+                                Alert::new(
+                                    AlertKind::StrategyErrorStreak,
+                                    format!("strategy_error_streak:{}", strategy_name),
+                                    format!("Strategy '{}' keeps failing", strategy_name),
+                                )
+                                .field("consecutive_failures", streak)
+                                .field("block", block_number)
+                                .field("error", e),
+                            )
+                            .await;
+                    }
                 }
             }
         }
@@ -9,7 +9,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio_stream::StreamExt;
 
-use crate::strategy::Strategy;
+use crate::{health, metrics, strategy::Strategy};
 
 const MAX_BLOCKS_PER_REQUEST: u64 = 10000;
 
@@ -19,6 +19,8 @@ pub struct LogCollector {
     provider: Option<Arc<dyn Provider>>,
     contract_address: Option<Address>,
     start_block: Option<u64>,
+    /// Stop the history backfill here instead of at the chain head
+    end_block: Option<u64>,
 }
 
 impl LogCollector {
@@ -29,6 +31,7 @@ impl LogCollector {
             provider: None,
             contract_address: None,
             start_block: None,
+            end_block: None,
         }
     }
 
@@ -39,6 +42,7 @@ impl LogCollector {
             provider: None,
             contract_address: Some(contract_address),
             start_block: Some(start_block),
+            end_block: None,
         }
     }
 
@@ -54,6 +58,12 @@ impl LogCollector {
         info!("Set starting block number: {}", block_number);
     }
 
+    /// Set the last block the history backfill reads, instead of the chain head
+    pub fn set_end_block(&mut self, block_number: u64) {
+        self.end_block = Some(block_number);
+        info!("Set ending block number: {}", block_number);
+    }
+
     /// Add a strategy to the collector
     pub async fn _add_strategy(&self, strategy: Box<dyn Strategy<Log>>) {
         let mut strategies = self.strategies.write().await;
@@ -157,7 +167,9 @@ impl LogCollector {
         );
 
         // First, get historical logs
-        let current_block = provider.get_block_number().await?;
+        let head_block = provider.get_block_number().await?;
+        metrics::set_head_block(head_block);
+        let current_block = self.end_block.map_or(head_block, |end| end.min(head_block));
 
         let mut start_block = start_block;
         let mut end_block = current_block;
@@ -184,6 +196,7 @@ impl LogCollector {
             for log in historical_logs {
                 self.execute_strategies(&log, provider).await;
             }
+            health::heartbeat();
 
             if end_block >= current_block || start_block >= current_block {
                 break;
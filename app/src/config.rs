@@ -1,4 +1,55 @@
 use alloy::primitives::{Address, address};
+
+/// Where liquidation candidates and their ordering come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CandidateSource {
+    /// Troves sorted by the locally computed ICR in the troves table
+    Database,
+    /// The on-chain `SortedTroves` list, mirrored locally. It is ordered by interest rate, so
+    /// every listed trove is scanned each block.
+    SortedTroves,
+    /// Troves whose precomputed liquidation price is above the oracle price
+    PriceIndex,
+}
+
+/// Settings for the redemption-opportunity strategy
+pub struct RedemptionConfig {
+    /// Chainlink-style BOLD/USD feed (8 decimals) used to detect BOLD trading below peg; without
+    /// one only urgent redemptions are considered, with BOLD costed at peg
+    pub bold_price_oracle: Option<Address>,
+    /// `CollateralRegistry`, used for the redemption fee and to execute redemptions
+    pub collateral_registry: Option<Address>,
+    /// Upper bound on BOLD redeemed in one go (18 decimals)
+    pub max_redemption_bold: u128,
+    /// Minimum estimated profit in USD (18 decimals) before an opportunity is reported
+    pub min_profit_usd: u128,
+    pub max_iterations: u64,
+    /// Minimum collateral accepted from an urgent redemption, in basis points of the expected amount
+    pub urgent_min_collateral_bps: u64,
+    /// Fee rate tolerated above the quoted one, in basis points, so a base rate bump from another
+    /// redemption in the same block does not revert ours
+    pub max_fee_headroom_bps: u64,
+    /// Send profitable redemptions through the `LiquidationExecutor` instead of only reporting
+    pub execute: bool,
+}
+
+impl RedemptionConfig {
+    /// Settings for a branch without redemption settings: urgent redemptions are sized and
+    /// reported once the branch shuts down, but never sent
+    pub fn urgent_only() -> Self {
+        Self {
+            bold_price_oracle: None,
+            collateral_registry: None,
+            max_redemption_bold: 100_000_000_000_000_000_000_000,
+            min_profit_usd: 0,
+            max_iterations: 20,
+            urgent_min_collateral_bps: 9_900,
+            max_fee_headroom_bps: 0,
+            execute: false,
+        }
+    }
+}
+
 pub struct ProtocolConfig {
   
     pub address_registry: Address,
@@ -7,7 +58,36 @@ pub struct ProtocolConfig {
     pub liquidator_address: Address,
    
     pub start_block: u64,
+    /// A `sqlite:` file, or a `postgres://` server for PostgreSQL storage
     pub database_url: &'static str,
+    pub reconcile_interval_secs: u64,
+    pub reconcile_batch_size: i64,
+    /// Chainlink-style feed pricing the gas token; `None` when gas is paid in the collateral
+    pub native_price_oracle: Option<Address>,
+    /// Below-peg redemption settings; `None` still follows the branch into urgent-redemption
+    /// mode with `RedemptionConfig::urgent_only`
+    pub redemption: Option<RedemptionConfig>,
+    pub candidate_source: CandidateSource,
+    /// How far ahead interest is accrued when precomputing liquidation prices
+    pub liquidation_price_horizon_secs: u64,
+    /// Listen address for the Prometheus `/metrics` and `/healthz`/`/readyz` endpoints
+    pub metrics_addr: &'static str,
+    /// Readiness fails once the processed block falls further behind head than this
+    pub max_block_lag: u64,
+    pub max_oracle_staleness_secs: u64,
+    /// Minimum signer balance (wei) for readiness
+    pub min_signer_balance_wei: u128,
+    /// Liveness fails once no collector has made progress for this long
+    pub max_stall_secs: u64,
+    /// Backup RPC endpoints tried in order when `rpc_url` stops answering
+    pub fallback_rpc_urls: &'static [&'static str],
+    /// Repeats of the same alert within this window are dropped
+    pub alert_dedup_secs: u64,
+    pub alert_max_per_minute: usize,
+    /// Consecutive failures of one strategy before an alert fires
+    pub strategy_error_streak: u32,
+    /// Simulate and record liquidations in `shadow_liquidations` instead of sending them
+    pub shadow_mode: bool,
 }
 
 pub fn get_info(protocol: &str) -> Option<ProtocolConfig> {
@@ -19,6 +99,22 @@ pub fn get_info(protocol: &str) -> Option<ProtocolConfig> {
             rpc_url: "https://rpc.hyperlend.finance/archive",
             start_block: 1093281,
             database_url: "sqlite:felix_main.db",
+            reconcile_interval_secs: 60,
+            reconcile_batch_size: 200,
+            native_price_oracle: None,
+            redemption: None,
+            candidate_source: CandidateSource::PriceIndex,
+            liquidation_price_horizon_secs: 3600,
+            metrics_addr: "0.0.0.0:9100",
+            max_block_lag: 5,
+            max_oracle_staleness_secs: 120,
+            min_signer_balance_wei: 50_000_000_000_000_000,
+            max_stall_secs: 300,
+            fallback_rpc_urls: &[],
+            alert_dedup_secs: 900,
+            alert_max_per_minute: 20,
+            strategy_error_streak: 5,
+            shadow_mode: false,
         }),
 
         "liquity" => Some(ProtocolConfig {
@@ -28,6 +124,22 @@ pub fn get_info(protocol: &str) -> Option<ProtocolConfig> {
             rpc_url: "https://eth.llamarpc.com",
             start_block:  21686212,
             database_url: "sqlite:liquity_main.db",
+            reconcile_interval_secs: 60,
+            reconcile_batch_size: 200,
+            native_price_oracle: None,
+            redemption: None,
+            candidate_source: CandidateSource::PriceIndex,
+            liquidation_price_horizon_secs: 3600,
+            metrics_addr: "0.0.0.0:9100",
+            max_block_lag: 5,
+            max_oracle_staleness_secs: 120,
+            min_signer_balance_wei: 50_000_000_000_000_000,
+            max_stall_secs: 300,
+            fallback_rpc_urls: &[],
+            alert_dedup_secs: 900,
+            alert_max_per_minute: 20,
+            strategy_error_streak: 5,
+            shadow_mode: false,
         }),
 
 
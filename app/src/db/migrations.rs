@@ -0,0 +1,562 @@
+use std::{future::Future, pin::Pin, str::FromStr};
+
+use alloy::primitives::{U256, U512};
+use eyre::Result;
+use log::info;
+use sqlx::{PgPool, SqliteConnection, SqlitePool};
+
+use super::u256::StoredU256;
+
+/// Data conversion SQL alone can't express, run after a migration's statements
+pub type Conversion =
+    for<'c> fn(&'c mut SqliteConnection) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'c>>;
+
+/// A forward schema change. Its statements, conversion and the version bump commit in one
+/// transaction.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub statements: &'static [&'static str],
+    pub convert: Option<Conversion>,
+}
+
+/// Every schema change in order. Append new migrations here; never edit one that has shipped.
+///
+/// Databases created before versioning have no `schema_version` table and start from 0. The
+/// `IF NOT EXISTS` clauses and the tolerated duplicate-column errors let them replay up to the
+/// version they were already at without losing data.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "last block checkpoint and troves",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS last_block (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                block_number INTEGER NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS troves (
+                trove_id TEXT PRIMARY KEY,
+                collateral TEXT NOT NULL,
+                debt TEXT NOT NULL,
+                interest_rate TEXT NOT NULL,
+                icr TEXT NOT NULL,   -- Collateral Ratio as string for precision (e.g., "115.23")
+                icr_numeric REAL NOT NULL,  -- Numeric version for sorting (e.g., 115.23)
+                -- 'active', 'zombie', 'closed_by_owner' or 'closed_by_liquidation'
+                status TEXT NOT NULL,
+                last_updated INTEGER NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_troves_trove_id ON troves(trove_id)",
+            "CREATE INDEX IF NOT EXISTS idx_troves_status ON troves(status)",
+            // Quick sorting by risk (lowest first)
+            "CREATE INDEX IF NOT EXISTS idx_troves_icr_numeric ON troves(icr_numeric)",
+            // Before the status lifecycle every closed trove was 'closed'. One still carrying
+            // debt was closed locally ahead of our liquidation, so the reconciler must check it
+            // against the chain; the rest were emptied on-chain.
+            r#"
+            UPDATE troves
+            SET status = CASE
+                WHEN debt NOT IN ('0', '') THEN 'closed_by_liquidation'
+                ELSE 'closed_by_owner'
+            END
+            WHERE status = 'closed'
+            "#,
+        ],
+        convert: None,
+    },
+    Migration {
+        version: 2,
+        description: "trove events and liquidation records",
+        statements: &[
+            // Every TroveOperation emitted for a trove
+            r#"
+            CREATE TABLE IF NOT EXISTS trove_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                trove_id TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                tx_hash TEXT NOT NULL,
+                log_index INTEGER NOT NULL,
+                operation TEXT NOT NULL,  -- TroveManager.Operation name, e.g. 'openTrove'
+                annual_interest_rate TEXT NOT NULL,
+                debt_increase_from_redist TEXT NOT NULL,
+                debt_increase_from_upfront_fee TEXT NOT NULL,
+                debt_change_from_operation TEXT NOT NULL,  -- signed
+                coll_increase_from_redist TEXT NOT NULL,
+                coll_change_from_operation TEXT NOT NULL,  -- signed
+                UNIQUE (tx_hash, log_index)
+            )
+            "#,
+            // Every Liquidation event with its realized outcome
+            r#"
+            CREATE TABLE IF NOT EXISTS liquidations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                branch TEXT NOT NULL,  -- TroveManager address
+                block_number INTEGER NOT NULL,
+                block_timestamp INTEGER NOT NULL,
+                tx_hash TEXT NOT NULL,
+                log_index INTEGER NOT NULL,
+                sender TEXT NOT NULL,
+                is_ours BOOLEAN NOT NULL,
+                debt_offset_by_sp TEXT NOT NULL,
+                debt_redistributed TEXT NOT NULL,
+                coll_gas_compensation TEXT NOT NULL,
+                bold_gas_compensation TEXT NOT NULL,
+                coll_sent_to_sp TEXT NOT NULL,
+                coll_redistributed TEXT NOT NULL,
+                coll_surplus TEXT NOT NULL,
+                price TEXT NOT NULL,
+                gas_used INTEGER NOT NULL,
+                effective_gas_price TEXT NOT NULL,
+                gas_cost TEXT NOT NULL,
+                net_profit_coll TEXT NOT NULL,  -- signed, collateral units
+                net_profit_usd TEXT NOT NULL,   -- signed, 18 decimals
+                gas_compensation_usd_numeric REAL NOT NULL,  -- for aggregation
+                net_profit_usd_numeric REAL NOT NULL,        -- for aggregation
+                UNIQUE (tx_hash, log_index)
+            )
+            "#,
+            // When our model first saw a trove as liquidatable
+            r#"
+            CREATE TABLE IF NOT EXISTS liquidation_candidates (
+                trove_id TEXT PRIMARY KEY,
+                first_liquidatable_block INTEGER NOT NULL,
+                last_seen_block INTEGER NOT NULL,
+                attempted BOOLEAN NOT NULL DEFAULT 0,
+                attempt_block INTEGER,
+                attempt_tx TEXT
+            )
+            "#,
+            // Who liquidated each trove and how fast
+            r#"
+            CREATE TABLE IF NOT EXISTS liquidated_troves (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                trove_id TEXT NOT NULL,
+                tx_hash TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                sender TEXT NOT NULL,
+                is_ours BOOLEAN NOT NULL,
+                effective_gas_price TEXT NOT NULL,
+                gas_price_gwei REAL NOT NULL,
+                liquidatable_since_block INTEGER,  -- NULL when our model never flagged it
+                blocks_after_eligible INTEGER,
+                we_attempted BOOLEAN NOT NULL,
+                UNIQUE (tx_hash, trove_id)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_trove_events_trove_id
+                ON trove_events(trove_id, block_number, log_index)",
+            "CREATE INDEX IF NOT EXISTS idx_liquidated_troves_sender ON liquidated_troves(sender)",
+            // Daily liquidation summaries
+            "CREATE INDEX IF NOT EXISTS idx_liquidations_branch_timestamp
+                ON liquidations(branch, block_timestamp)",
+        ],
+        convert: None,
+    },
+    Migration {
+        version: 3,
+        description: "trove liquidation price index",
+        statements: &[
+            // Block timestamp of the last debt update
+            "ALTER TABLE troves ADD COLUMN last_updated_at INTEGER NOT NULL DEFAULT 0",
+            // Price (USD, 32-byte big-endian U256 with 18 decimals) at which ICR reaches MCR,
+            // and the timestamp interest is accrued to
+            "ALTER TABLE troves ADD COLUMN liquidation_price BLOB NOT NULL
+                DEFAULT X'0000000000000000000000000000000000000000000000000000000000000000'",
+            "ALTER TABLE troves ADD COLUMN liquidation_price_until INTEGER NOT NULL DEFAULT 0",
+            // The per-block price-trigger range query
+            "CREATE INDEX IF NOT EXISTS idx_troves_liquidation_price
+                ON troves(status, liquidation_price)",
+            // Refreshing expired liquidation prices
+            "CREATE INDEX IF NOT EXISTS idx_troves_liquidation_price_until
+                ON troves(liquidation_price_until)",
+        ],
+        convert: None,
+    },
+    Migration {
+        version: 4,
+        description: "shadow liquidations",
+        statements: &[
+            // What shadow mode would have sent, one row per trove
+            r#"
+            CREATE TABLE IF NOT EXISTS shadow_liquidations (
+                trove_id TEXT PRIMARY KEY,
+                first_block INTEGER NOT NULL,
+                last_block INTEGER NOT NULL,
+                batch_size INTEGER NOT NULL,
+                estimated_gas INTEGER,  -- NULL when the simulation reverted
+                gas_price TEXT NOT NULL,
+                expected_coll_compensation TEXT NOT NULL,
+                expected_eth_compensation TEXT NOT NULL,
+                simulated_ok BOOLEAN NOT NULL,
+                error TEXT
+            )
+            "#,
+            // Comparing shadow decisions with actual liquidations
+            "CREATE INDEX IF NOT EXISTS idx_liquidated_troves_trove_id
+                ON liquidated_troves(trove_id)",
+        ],
+        convert: None,
+    },
+    Migration {
+        version: 5,
+        description: "trove amounts as big-endian BLOBs with an ICR sort key",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS troves_u256 (
+                trove_id TEXT PRIMARY KEY,
+                collateral BLOB NOT NULL,     -- 32-byte big-endian U256
+                debt BLOB NOT NULL,           -- 32-byte big-endian U256
+                icr_key BLOB NOT NULL,        -- collateral * 1e18 / debt, see `Trove::icr_key`
+                interest_rate BLOB NOT NULL,  -- 32-byte big-endian U256
+                status TEXT NOT NULL,
+                last_updated INTEGER NOT NULL,
+                last_updated_at INTEGER NOT NULL DEFAULT 0,
+                liquidation_price BLOB NOT NULL,  -- 32-byte big-endian U256, 18 decimals
+                liquidation_price_until INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        ],
+        convert: Some(troves_to_blobs),
+    },
+];
+
+/// The PostgreSQL schema. Its baseline is SQLite's version 5, so later changes are appended to
+/// both lists under the same version number. Conversions are SQLite-only.
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 5,
+        description: "baseline schema",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS last_block (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                block_number BIGINT NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS troves (
+                trove_id TEXT PRIMARY KEY,
+                collateral BYTEA NOT NULL,     -- 32-byte big-endian U256
+                debt BYTEA NOT NULL,           -- 32-byte big-endian U256
+                icr_key BYTEA NOT NULL,        -- collateral * 1e18 / debt, see `Trove::icr_key`
+                interest_rate BYTEA NOT NULL,  -- 32-byte big-endian U256
+                status TEXT NOT NULL,
+                last_updated BIGINT NOT NULL,
+                last_updated_at BIGINT NOT NULL DEFAULT 0,
+                liquidation_price BYTEA NOT NULL,  -- 32-byte big-endian U256, 18 decimals
+                liquidation_price_until BIGINT NOT NULL DEFAULT 0
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS trove_events (
+                id BIGSERIAL PRIMARY KEY,
+                trove_id TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                tx_hash TEXT NOT NULL,
+                log_index BIGINT NOT NULL,
+                operation TEXT NOT NULL,
+                annual_interest_rate TEXT NOT NULL,
+                debt_increase_from_redist TEXT NOT NULL,
+                debt_increase_from_upfront_fee TEXT NOT NULL,
+                debt_change_from_operation TEXT NOT NULL,
+                coll_increase_from_redist TEXT NOT NULL,
+                coll_change_from_operation TEXT NOT NULL,
+                UNIQUE (tx_hash, log_index)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS liquidations (
+                id BIGSERIAL PRIMARY KEY,
+                branch TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_timestamp BIGINT NOT NULL,
+                tx_hash TEXT NOT NULL,
+                log_index BIGINT NOT NULL,
+                sender TEXT NOT NULL,
+                is_ours BOOLEAN NOT NULL,
+                debt_offset_by_sp TEXT NOT NULL,
+                debt_redistributed TEXT NOT NULL,
+                coll_gas_compensation TEXT NOT NULL,
+                bold_gas_compensation TEXT NOT NULL,
+                coll_sent_to_sp TEXT NOT NULL,
+                coll_redistributed TEXT NOT NULL,
+                coll_surplus TEXT NOT NULL,
+                price TEXT NOT NULL,
+                gas_used BIGINT NOT NULL,
+                effective_gas_price TEXT NOT NULL,
+                gas_cost TEXT NOT NULL,
+                net_profit_coll TEXT NOT NULL,
+                net_profit_usd TEXT NOT NULL,
+                gas_compensation_usd_numeric DOUBLE PRECISION NOT NULL,
+                net_profit_usd_numeric DOUBLE PRECISION NOT NULL,
+                UNIQUE (tx_hash, log_index)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS liquidation_candidates (
+                trove_id TEXT PRIMARY KEY,
+                first_liquidatable_block BIGINT NOT NULL,
+                last_seen_block BIGINT NOT NULL,
+                attempted BOOLEAN NOT NULL DEFAULT FALSE,
+                attempt_block BIGINT,
+                attempt_tx TEXT
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS liquidated_troves (
+                id BIGSERIAL PRIMARY KEY,
+                trove_id TEXT NOT NULL,
+                tx_hash TEXT NOT NULL,
+                block_number BIGINT NOT NULL,
+                sender TEXT NOT NULL,
+                is_ours BOOLEAN NOT NULL,
+                effective_gas_price TEXT NOT NULL,
+                gas_price_gwei DOUBLE PRECISION NOT NULL,
+                liquidatable_since_block BIGINT,
+                blocks_after_eligible BIGINT,
+                we_attempted BOOLEAN NOT NULL,
+                UNIQUE (tx_hash, trove_id)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS shadow_liquidations (
+                trove_id TEXT PRIMARY KEY,
+                first_block BIGINT NOT NULL,
+                last_block BIGINT NOT NULL,
+                batch_size BIGINT NOT NULL,
+                estimated_gas BIGINT,
+                gas_price TEXT NOT NULL,
+                expected_coll_compensation TEXT NOT NULL,
+                expected_eth_compensation TEXT NOT NULL,
+                simulated_ok BOOLEAN NOT NULL,
+                error TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_troves_status ON troves(status)",
+            "CREATE INDEX IF NOT EXISTS idx_troves_icr_key ON troves(status, icr_key)",
+            "CREATE INDEX IF NOT EXISTS idx_troves_liquidation_price
+                ON troves(status, liquidation_price)",
+            "CREATE INDEX IF NOT EXISTS idx_troves_liquidation_price_until
+                ON troves(liquidation_price_until)",
+            "CREATE INDEX IF NOT EXISTS idx_trove_events_trove_id
+                ON trove_events(trove_id, block_number, log_index)",
+            "CREATE INDEX IF NOT EXISTS idx_liquidated_troves_sender ON liquidated_troves(sender)",
+            "CREATE INDEX IF NOT EXISTS idx_liquidated_troves_trove_id ON liquidated_troves(trove_id)",
+            "CREATE INDEX IF NOT EXISTS idx_liquidations_branch_timestamp
+                ON liquidations(branch, block_timestamp)",
+        ],
+        convert: None,
+    },
+];
+
+/// Indices on `troves` once amounts are BLOBs; dropping the TEXT table dropped the old ones
+const TROVE_INDICES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_troves_status ON troves(status)",
+    // Quick sorting by risk (lowest first)
+    "CREATE INDEX IF NOT EXISTS idx_troves_icr_key ON troves(status, icr_key)",
+    "CREATE INDEX IF NOT EXISTS idx_troves_liquidation_price
+        ON troves(status, liquidation_price)",
+    "CREATE INDEX IF NOT EXISTS idx_troves_liquidation_price_until
+        ON troves(liquidation_price_until)",
+];
+
+/// Schema version this binary migrates to
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Version the database is at, 0 when it has never been migrated
+pub async fn current_version(pool: &SqlitePool) -> Result<i64> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL  -- unix timestamp
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Apply every migration newer than the database, refusing databases written by a newer binary.
+/// Returns the version the database is at afterwards.
+pub async fn migrate(pool: &SqlitePool) -> Result<i64> {
+    let current = current_version(pool).await?;
+    let latest = latest_version();
+    if current > latest {
+        return Err(eyre::eyre!(
+            "Database schema is at version {} but this binary only knows up to {}; upgrade the bot",
+            current,
+            latest
+        ));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|migration| migration.version > current) {
+        let mut tx = pool.begin().await?;
+        apply(&mut tx, migration).await.map_err(|e| {
+            eyre::eyre!("Migration {} ({}) failed: {}", migration.version, migration.description, e)
+        })?;
+        tx.commit().await?;
+        info!("🗄️ Applied schema migration {} - {}", migration.version, migration.description);
+    }
+
+    Ok(latest)
+}
+
+/// `migrate` for PostgreSQL: apply every migration in `POSTGRES_MIGRATIONS` newer than the
+/// database. Returns the version the database is at afterwards.
+pub async fn migrate_postgres(pool: &PgPool) -> Result<i64> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version BIGINT PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at BIGINT NOT NULL  -- unix timestamp
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    let current: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    let current = current.unwrap_or(0);
+    let latest = POSTGRES_MIGRATIONS.last().map_or(0, |migration| migration.version);
+    if current > latest {
+        return Err(eyre::eyre!(
+            "Database schema is at version {} but this binary only knows up to {}; upgrade the bot",
+            current,
+            latest
+        ));
+    }
+
+    for migration in POSTGRES_MIGRATIONS.iter().filter(|migration| migration.version > current) {
+        let mut tx = pool.begin().await?;
+        for statement in migration.statements {
+            sqlx::query(statement).execute(&mut *tx).await.map_err(|e| {
+                eyre::eyre!(
+                    "Migration {} ({}) failed: {}",
+                    migration.version,
+                    migration.description,
+                    e
+                )
+            })?;
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, applied_at)
+             VALUES ($1, $2, EXTRACT(EPOCH FROM now())::BIGINT)",
+        )
+        .bind(migration.version)
+        .bind(migration.description)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        info!("🗄️ Applied schema migration {} - {}", migration.version, migration.description);
+    }
+
+    Ok(latest)
+}
+
+async fn apply(conn: &mut SqliteConnection, migration: &Migration) -> Result<()> {
+    for statement in migration.statements {
+        match sqlx::query(statement).execute(&mut *conn).await {
+            Ok(_) => {}
+            Err(e) if is_duplicate_column(&e) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if let Some(convert) = migration.convert {
+        convert(&mut *conn).await?;
+    }
+    sqlx::query(
+        "INSERT INTO schema_version (version, description, applied_at)
+         VALUES (?, ?, strftime('%s', 'now'))",
+    )
+    .bind(migration.version)
+    .bind(migration.description)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// A trove row as stored before amounts became BLOBs
+type TextTroveRow = (String, String, String, String, String, i64, i64, StoredU256, i64);
+
+/// `collateral * 1e18 / debt`, saturating, with zero debt sorting last. Kept here rather than
+/// calling `Trove::icr_key` so this migration keeps writing the keys it shipped with.
+fn icr_key(coll: U256, debt: U256) -> U256 {
+    if debt.is_zero() {
+        return U256::MAX;
+    }
+    let key = U512::from(coll) * U512::from(1_000_000_000_000_000_000u64) / U512::from(debt);
+    U256::saturating_from(key)
+}
+
+/// Copy every trove from the TEXT columns into `troves_u256`, then swap the tables
+fn troves_to_blobs(
+    conn: &mut SqliteConnection,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+    Box::pin(async move {
+        let rows: Vec<TextTroveRow> =
+            sqlx::query_as(
+                r#"
+                SELECT trove_id, collateral, debt, interest_rate, status, last_updated,
+                    last_updated_at, liquidation_price, liquidation_price_until
+                FROM troves
+                "#,
+            )
+            .fetch_all(&mut *conn)
+            .await?;
+
+        for (trove_id, coll, debt, rate, status, updated, updated_at, price, until) in rows {
+            let parse = |value: &str| {
+                U256::from_str(value).map_err(|e| {
+                    eyre::eyre!("Trove {} has invalid amount {}: {}", trove_id, value, e)
+                })
+            };
+            let (coll, debt, rate) = (parse(&coll)?, parse(&debt)?, parse(&rate)?);
+            sqlx::query(
+                r#"
+                INSERT INTO troves_u256 (trove_id, collateral, debt, icr_key, interest_rate,
+                    status, last_updated, last_updated_at, liquidation_price,
+                    liquidation_price_until)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&trove_id)
+            .bind(StoredU256(coll))
+            .bind(StoredU256(debt))
+            .bind(StoredU256(icr_key(coll, debt)))
+            .bind(StoredU256(rate))
+            .bind(status)
+            .bind(updated)
+            .bind(updated_at)
+            .bind(price)
+            .bind(until)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        sqlx::query("DROP TABLE troves").execute(&mut *conn).await?;
+        sqlx::query("ALTER TABLE troves_u256 RENAME TO troves").execute(&mut *conn).await?;
+        for statement in TROVE_INDICES {
+            sqlx::query(statement).execute(&mut *conn).await?;
+        }
+        Ok(())
+    })
+}
+
+/// Columns added before versioning existed are already present in older unversioned databases
+fn is_duplicate_column(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(e) if e.message().contains("duplicate column name"))
+}
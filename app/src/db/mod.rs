@@ -1,5 +1,8 @@
 pub mod init;
+pub mod migrations;
+pub mod postgres;
+pub mod sqlite;
 pub mod store;
+pub mod u256;
 pub use init::initialize_database;
 pub use store::DatabaseStore;
-
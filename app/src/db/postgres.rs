@@ -0,0 +1,621 @@
+use alloy::primitives::{U256, Uint};
+use eyre::Result;
+use sqlx::PgPool;
+
+use super::store::{
+    CompetitorStats, DatabaseStore, LiquidatedTrove, LiquidatedTroveInsert, LiquidationCandidate,
+    LiquidationInsert, LiquidationRecord, LiquidationSummary, ShadowComparison,
+    ShadowLiquidationInsert, Trove, TroveEvent, TroveEventInsert, TroveStatus,
+};
+use super::u256::StoredU256;
+
+/// A store on a shared PostgreSQL server, selected by a `postgres://` database URL. Queries
+/// mirror `SqliteStore`; amounts are BYTEA, which sorts bytewise like SQLite's BLOBs.
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+impl PostgresStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabaseStore for PostgresStore {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn get_last_block(&self) -> Result<i64> {
+        let last_block = sqlx::query_scalar::<_, i64>("SELECT block_number FROM last_block")
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(last_block.unwrap_or(0))
+    }
+
+    async fn set_last_block(&self, block_number: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO last_block (id, block_number) VALUES (1, $1)
+            ON CONFLICT(id) DO UPDATE SET block_number = excluded.block_number
+            "#,
+        )
+        .bind(block_number)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ========== Troves Table Methods ==========
+
+    async fn get_lowest_icr_troves(&self, limit: i64) -> Result<Vec<Trove>> {
+        let troves = sqlx::query_as::<_, Trove>(
+            r#"
+            SELECT * FROM troves
+            WHERE status IN ('active', 'zombie')
+            ORDER BY icr_key ASC, trove_id ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(troves)
+    }
+
+    async fn get_troves_by_interest_rate(&self, limit: i64) -> Result<Vec<Trove>> {
+        let troves = sqlx::query_as::<_, Trove>(
+            r#"
+            SELECT * FROM troves
+            WHERE status IN ('active', 'zombie')
+            ORDER BY interest_rate ASC, trove_id ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(troves)
+    }
+
+    async fn get_troves_by_debt(&self, limit: i64) -> Result<Vec<Trove>> {
+        let troves = sqlx::query_as::<_, Trove>(
+            r#"
+            SELECT * FROM troves
+            WHERE status IN ('active', 'zombie')
+            ORDER BY debt DESC, trove_id ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(troves)
+    }
+
+    async fn get_open_troves(&self) -> Result<Vec<Trove>> {
+        let troves = sqlx::query_as::<_, Trove>(
+            "SELECT * FROM troves WHERE status IN ('active', 'zombie')",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(troves)
+    }
+
+    async fn get_reconcilable_troves_after(&self, cursor: &str, limit: i64) -> Result<Vec<Trove>> {
+        let troves = sqlx::query_as::<_, Trove>(
+            r#"
+            SELECT * FROM troves
+            WHERE (status IN ('active', 'zombie')
+                OR (status = 'closed_by_liquidation' AND debt <> $1))
+            AND trove_id > $2
+            ORDER BY trove_id ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(StoredU256(U256::ZERO))
+        .bind(cursor)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(troves)
+    }
+
+    async fn get_trove_by_id(&self, trove_id: &str) -> Result<Option<Trove>> {
+        let trove = sqlx::query_as::<_, Trove>("SELECT * FROM troves WHERE trove_id = $1")
+            .bind(trove_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(trove)
+    }
+
+    async fn upsert_trove(&self, trove: &Trove) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO troves (trove_id, collateral, debt, icr_key, interest_rate, status,
+                last_updated, last_updated_at, liquidation_price, liquidation_price_until)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT(trove_id) DO UPDATE SET
+                collateral = excluded.collateral,
+                debt = excluded.debt,
+                icr_key = excluded.icr_key,
+                interest_rate = excluded.interest_rate,
+                status = excluded.status,
+                last_updated = excluded.last_updated,
+                last_updated_at = excluded.last_updated_at,
+                liquidation_price = excluded.liquidation_price,
+                liquidation_price_until = excluded.liquidation_price_until
+            "#,
+        )
+        .bind(&trove.trove_id)
+        .bind(trove.collateral)
+        .bind(trove.debt)
+        .bind(trove.icr_key)
+        .bind(trove.interest_rate)
+        .bind(&trove.status)
+        .bind(trove.last_updated)
+        .bind(trove.last_updated_at)
+        .bind(trove.liquidation_price)
+        .bind(trove.liquidation_price_until)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+     }
+
+    async fn set_liquidation_price(
+        &self,
+        trove_id: &str,
+        last_updated_at: i64,
+        liquidation_price: U256,
+        liquidation_price_until: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE troves
+            SET last_updated_at = $1, liquidation_price = $2, liquidation_price_until = $3
+            WHERE trove_id = $4
+            "#,
+        )
+        .bind(last_updated_at)
+        .bind(StoredU256(liquidation_price))
+        .bind(liquidation_price_until)
+        .bind(trove_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn set_trove_status(
+        &self,
+        trove_id: &str,
+        status: TroveStatus,
+        block_number: i64,
+    ) -> Result<()> {
+        sqlx::query("UPDATE troves SET status = $1, last_updated = $2 WHERE trove_id = $3")
+            .bind(status.as_str())
+            .bind(block_number)
+            .bind(trove_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn close_troves(&self, trove_ids: &[Uint<256, 4>], block_number: i64) -> Result<()> {
+        if trove_ids.is_empty() {
+            return Ok(());
+        }
+
+        // Trove ids are stored as decimal strings
+        let ids: Vec<String> = trove_ids.iter().map(|id| id.to_string()).collect();
+        sqlx::query(
+            r#"
+            UPDATE troves
+            SET status = 'closed_by_liquidation', last_updated = $1
+            WHERE trove_id = ANY($2)
+            AND status IN ('active', 'zombie')
+            "#,
+        )
+        .bind(block_number)
+        .bind(ids)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ========== Trove Events Table Methods ==========
+
+    async fn insert_trove_event(&self, event: &TroveEventInsert) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO trove_events (
+                trove_id, block_number, tx_hash, log_index, operation, annual_interest_rate,
+                debt_increase_from_redist, debt_increase_from_upfront_fee,
+                debt_change_from_operation, coll_increase_from_redist, coll_change_from_operation
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT(tx_hash, log_index) DO NOTHING
+            "#,
+        )
+        .bind(&event.trove_id)
+        .bind(event.block_number)
+        .bind(&event.tx_hash)
+        .bind(event.log_index)
+        .bind(&event.operation)
+        .bind(&event.annual_interest_rate)
+        .bind(&event.debt_increase_from_redist)
+        .bind(&event.debt_increase_from_upfront_fee)
+        .bind(&event.debt_change_from_operation)
+        .bind(&event.coll_increase_from_redist)
+        .bind(&event.coll_change_from_operation)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_trove_timeline(&self, trove_id: &str) -> Result<Vec<TroveEvent>> {
+        let events = sqlx::query_as::<_, TroveEvent>(
+            r#"
+            SELECT * FROM trove_events
+            WHERE trove_id = $1
+            ORDER BY block_number ASC, log_index ASC
+            "#,
+        )
+        .bind(trove_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(events)
+    }
+
+    async fn get_trove_timeline_between(
+        &self,
+        trove_id: &str,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<Vec<TroveEvent>> {
+        let events = sqlx::query_as::<_, TroveEvent>(
+            r#"
+            SELECT * FROM trove_events
+            WHERE trove_id = $1 AND block_number BETWEEN $2 AND $3
+            ORDER BY block_number ASC, log_index ASC
+            "#,
+        )
+        .bind(trove_id)
+        .bind(from_block)
+        .bind(to_block)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(events)
+    }
+
+    async fn get_last_trove_event(&self, trove_id: &str) -> Result<Option<TroveEvent>> {
+        let event = sqlx::query_as::<_, TroveEvent>(
+            r#"
+            SELECT * FROM trove_events
+            WHERE trove_id = $1
+            ORDER BY block_number DESC, log_index DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(trove_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(event)
+    }
+
+    // ========== Liquidations Table Methods ==========
+
+    async fn insert_liquidation(&self, record: &LiquidationInsert) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO liquidations (
+                branch, block_number, block_timestamp, tx_hash, log_index, sender, is_ours,
+                debt_offset_by_sp, debt_redistributed, coll_gas_compensation, bold_gas_compensation,
+                coll_sent_to_sp, coll_redistributed, coll_surplus, price, gas_used,
+                effective_gas_price, gas_cost, net_profit_coll, net_profit_usd,
+                gas_compensation_usd_numeric, net_profit_usd_numeric
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11,
+                $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22
+            )
+            ON CONFLICT(tx_hash, log_index) DO NOTHING
+            "#,
+        )
+        .bind(&record.branch)
+        .bind(record.block_number)
+        .bind(record.block_timestamp)
+        .bind(&record.tx_hash)
+        .bind(record.log_index)
+        .bind(&record.sender)
+        .bind(record.is_ours)
+        .bind(&record.debt_offset_by_sp)
+        .bind(&record.debt_redistributed)
+        .bind(&record.coll_gas_compensation)
+        .bind(&record.bold_gas_compensation)
+        .bind(&record.coll_sent_to_sp)
+        .bind(&record.coll_redistributed)
+        .bind(&record.coll_surplus)
+        .bind(&record.price)
+        .bind(record.gas_used)
+        .bind(&record.effective_gas_price)
+        .bind(&record.gas_cost)
+        .bind(&record.net_profit_coll)
+        .bind(&record.net_profit_usd)
+        .bind(record.gas_compensation_usd_numeric)
+        .bind(record.net_profit_usd_numeric)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_liquidations_by_tx(&self, tx_hash: &str) -> Result<Vec<LiquidationRecord>> {
+        let records = sqlx::query_as::<_, LiquidationRecord>(
+            "SELECT * FROM liquidations WHERE tx_hash = $1 ORDER BY log_index ASC",
+        )
+        .bind(tx_hash)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(records)
+    }
+
+    async fn get_liquidation_summary_by_day(&self) -> Result<Vec<LiquidationSummary>> {
+        let summaries = sqlx::query_as::<_, LiquidationSummary>(
+            r#"
+            SELECT
+                to_char(to_timestamp(block_timestamp) AT TIME ZONE 'UTC', 'YYYY-MM-DD') AS period,
+                branch,
+                COUNT(*) AS liquidations,
+                COALESCE(SUM(CASE WHEN is_ours THEN 1 ELSE 0 END), 0) AS ours,
+                COALESCE(SUM(CASE WHEN is_ours THEN gas_compensation_usd_numeric ELSE 0 END), 0.0)
+                    AS our_gas_compensation_usd,
+                COALESCE(SUM(CASE WHEN is_ours THEN net_profit_usd_numeric ELSE 0 END), 0.0)
+                    AS our_net_profit_usd
+            FROM liquidations
+            GROUP BY period, branch
+            ORDER BY period DESC, branch ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(summaries)
+    }
+
+    async fn get_liquidation_summary_by_branch(&self) -> Result<Vec<LiquidationSummary>> {
+        let summaries = sqlx::query_as::<_, LiquidationSummary>(
+            r#"
+            SELECT
+                'all' AS period,
+                branch,
+                COUNT(*) AS liquidations,
+                COALESCE(SUM(CASE WHEN is_ours THEN 1 ELSE 0 END), 0) AS ours,
+                COALESCE(SUM(CASE WHEN is_ours THEN gas_compensation_usd_numeric ELSE 0 END), 0.0)
+                    AS our_gas_compensation_usd,
+                COALESCE(SUM(CASE WHEN is_ours THEN net_profit_usd_numeric ELSE 0 END), 0.0)
+                    AS our_net_profit_usd
+            FROM liquidations
+            GROUP BY branch
+            ORDER BY branch ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(summaries)
+    }
+
+    // ========== Competitor Analytics Methods ==========
+
+    async fn record_liquidation_candidates(
+        &self,
+        trove_ids: &[Uint<256, 4>],
+        block_number: i64,
+    ) -> Result<()> {
+        for id in trove_ids {
+            sqlx::query(
+                r#"
+                INSERT INTO liquidation_candidates
+                    (trove_id, first_liquidatable_block, last_seen_block)
+                VALUES ($1, $2, $3)
+                ON CONFLICT(trove_id) DO UPDATE SET last_seen_block = excluded.last_seen_block
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(block_number)
+            .bind(block_number)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn mark_candidates_attempted(
+        &self,
+        trove_ids: &[Uint<256, 4>],
+        block_number: i64,
+        tx_hash: Option<&str>,
+    ) -> Result<()> {
+        for id in trove_ids {
+            sqlx::query(
+                r#"
+                UPDATE liquidation_candidates
+                SET attempted = TRUE, attempt_block = $1, attempt_tx = COALESCE($2, attempt_tx)
+                WHERE trove_id = $3
+                "#,
+            )
+            .bind(block_number)
+            .bind(tx_hash)
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_liquidation_candidate(
+        &self,
+        trove_id: &str,
+    ) -> Result<Option<LiquidationCandidate>> {
+        let candidate = sqlx::query_as::<_, LiquidationCandidate>(
+            "SELECT * FROM liquidation_candidates WHERE trove_id = $1",
+        )
+        .bind(trove_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(candidate)
+    }
+
+    async fn get_troves_liquidated_in_tx(&self, tx_hash: &str) -> Result<Vec<String>> {
+        let trove_ids = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT trove_id FROM trove_events
+            WHERE tx_hash = $1 AND operation = 'liquidate'
+            ORDER BY log_index ASC
+            "#,
+        )
+        .bind(tx_hash)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(trove_ids)
+    }
+
+    async fn insert_liquidated_trove(&self, trove: &LiquidatedTroveInsert) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO liquidated_troves (
+                trove_id, tx_hash, block_number, sender, is_ours, effective_gas_price,
+                gas_price_gwei, liquidatable_since_block, blocks_after_eligible, we_attempted
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT(tx_hash, trove_id) DO NOTHING
+            "#,
+        )
+        .bind(&trove.trove_id)
+        .bind(&trove.tx_hash)
+        .bind(trove.block_number)
+        .bind(&trove.sender)
+        .bind(trove.is_ours)
+        .bind(&trove.effective_gas_price)
+        .bind(trove.gas_price_gwei)
+        .bind(trove.liquidatable_since_block)
+        .bind(trove.blocks_after_eligible)
+        .bind(trove.we_attempted)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_lost_liquidations(&self, limit: i64) -> Result<Vec<LiquidatedTrove>> {
+        let troves = sqlx::query_as::<_, LiquidatedTrove>(
+            r#"
+            SELECT * FROM liquidated_troves
+            WHERE NOT is_ours
+            ORDER BY block_number DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(troves)
+    }
+
+    async fn get_competitor_stats(&self) -> Result<Vec<CompetitorStats>> {
+        let stats = sqlx::query_as::<_, CompetitorStats>(
+            r#"
+            SELECT
+                sender,
+                COUNT(*) AS troves,
+                COUNT(liquidatable_since_block) AS tracked,
+                COALESCE(SUM(CASE WHEN we_attempted THEN 1 ELSE 0 END), 0) AS we_attempted,
+                AVG(blocks_after_eligible)::DOUBLE PRECISION AS avg_blocks_after_eligible,
+                MIN(blocks_after_eligible) AS min_blocks_after_eligible,
+                AVG(gas_price_gwei) AS avg_gas_price_gwei,
+                MAX(gas_price_gwei) AS max_gas_price_gwei
+            FROM liquidated_troves
+            WHERE NOT is_ours
+            GROUP BY sender
+            ORDER BY troves DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(stats)
+    }
+
+    // ========== Shadow Liquidation Methods ==========
+
+    async fn record_shadow_liquidation(&self, record: &ShadowLiquidationInsert) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO shadow_liquidations (
+                trove_id, first_block, last_block, batch_size, estimated_gas, gas_price,
+                expected_coll_compensation, expected_eth_compensation, simulated_ok, error
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT(trove_id) DO UPDATE SET
+                last_block = excluded.last_block,
+                batch_size = excluded.batch_size,
+                estimated_gas = excluded.estimated_gas,
+                gas_price = excluded.gas_price,
+                expected_coll_compensation = excluded.expected_coll_compensation,
+                expected_eth_compensation = excluded.expected_eth_compensation,
+                simulated_ok = excluded.simulated_ok,
+                error = excluded.error
+            "#,
+        )
+        .bind(&record.trove_id)
+        .bind(record.block_number)
+        .bind(record.block_number)
+        .bind(record.batch_size)
+        .bind(record.estimated_gas)
+        .bind(&record.gas_price)
+        .bind(&record.expected_coll_compensation)
+        .bind(&record.expected_eth_compensation)
+        .bind(record.simulated_ok)
+        .bind(&record.error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn has_shadow_liquidation(&self, trove_id: &str) -> Result<bool> {
+        let found = sqlx::query_scalar::<_, String>(
+            "SELECT trove_id FROM shadow_liquidations WHERE trove_id = $1",
+        )
+        .bind(trove_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(found.is_some())
+    }
+
+    async fn get_shadow_comparison(&self, limit: i64) -> Result<Vec<ShadowComparison>> {
+        let rows = sqlx::query_as::<_, ShadowComparison>(
+            r#"
+            SELECT
+                s.trove_id,
+                s.first_block AS shadow_block,
+                s.simulated_ok,
+                s.estimated_gas,
+                s.expected_coll_compensation,
+                l.block_number AS actual_block,
+                l.sender AS actual_sender,
+                l.block_number - s.first_block AS blocks_after_shadow
+            FROM shadow_liquidations s
+            -- Only the first liquidation at or after the shadow decision
+            LEFT JOIN liquidated_troves l ON l.id = (
+                SELECT id FROM liquidated_troves
+                WHERE trove_id = s.trove_id AND block_number >= s.first_block
+                ORDER BY block_number, id
+                LIMIT 1
+            )
+            ORDER BY s.first_block DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+}
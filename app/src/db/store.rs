@@ -1,11 +1,14 @@
-use alloy::primitives::{Address, Uint};
-use chrono::Utc;
+use alloy::primitives::{Address, U256, U512, Uint};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::FromRow;
 
 use eyre::Result;
 use std::str::FromStr;
 
+use super::u256::StoredU256;
+
+const DECIMAL_PRECISION: u64 = 1_000_000_000_000_000_000;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserCollateral {
     pub id: i64,
@@ -77,165 +80,337 @@ impl AssetConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Trove {
     pub trove_id: String,
-    pub collateral: String,
-    pub debt: String,
-    pub icr: String,
-    pub icr_numeric: f64,
-    pub interest_rate: String,
+    pub collateral: StoredU256,
+    pub debt: StoredU256,
+    /// Sort key ordering troves by ICR, see [`Trove::icr_key`]
+    pub icr_key: StoredU256,
+    pub interest_rate: StoredU256,
     pub status: String,
     pub last_updated: i64,
+    /// Block timestamp of the last debt update, the start of interest accrual
+    pub last_updated_at: i64,
+    /// Oracle price (18 decimals) below which the trove's ICR is under MCR, with interest accrued
+    /// up to `liquidation_price_until`
+    pub liquidation_price: StoredU256,
+    pub liquidation_price_until: i64,
 }
 
-pub struct DatabaseStore {
-    pool: SqlitePool,
-}
+impl Trove {
+    pub fn collateral(&self) -> U256 {
+        self.collateral.0
+    }
 
-impl DatabaseStore {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn debt(&self) -> U256 {
+        self.debt.0
     }
 
-    pub async fn get_last_block(&self) -> Result<i64> {
-        let last_block = sqlx::query_scalar::<_, i64>("SELECT block_number FROM last_block")
-            .fetch_optional(&self.pool)
-            .await?;
-        Ok(last_block.unwrap_or(0))
+    pub fn interest_rate(&self) -> U256 {
+        self.interest_rate.0
     }
 
-    pub async fn set_last_block(&self, block_number: i64) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO last_block (id, block_number) VALUES (1, ?)
-            ON CONFLICT(id) DO UPDATE SET block_number = excluded.block_number
-            "#,
-        )
-        .bind(block_number)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+    /// Collateral per unit of recorded debt (18 decimals). At any single price troves sort by
+    /// this exactly as they sort by ICR; zero debt sorts last.
+    pub fn icr_key(coll: U256, debt: U256) -> StoredU256 {
+        if debt.is_zero() {
+            return StoredU256(U256::MAX);
+        }
+        let key = U512::from(coll) * U512::from(DECIMAL_PRECISION) / U512::from(debt);
+        StoredU256(U256::saturating_from(key))
     }
+}
 
-    // ========== Troves Table Methods ==========
+/// A decoded `TroveOperation` event for a single trove
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TroveEvent {
+    pub id: i64,
+    pub trove_id: String,
+    pub block_number: i64,
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub operation: String,
+    pub annual_interest_rate: String,
+    pub debt_increase_from_redist: String,
+    pub debt_increase_from_upfront_fee: String,
+    pub debt_change_from_operation: String,
+    pub coll_increase_from_redist: String,
+    pub coll_change_from_operation: String,
+}
 
-    pub async fn get_all_active_troves(&self) -> Result<Vec<Trove>> {
-        let troves = sqlx::query_as::<_, Trove>("SELECT * FROM troves WHERE status = 'active'  ORDER BY icr_numeric ASC LIMIT 50")
-            .fetch_all(&self.pool)
-            .await?;
-        Ok(troves)
-    }
+/// A shadow-mode liquidation decision joined with the trove's actual on-chain liquidation
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ShadowComparison {
+    pub trove_id: String,
+    pub shadow_block: i64,
+    pub simulated_ok: bool,
+    pub estimated_gas: Option<i64>,
+    pub expected_coll_compensation: String,
+    /// NULL until someone actually liquidates the trove
+    pub actual_block: Option<i64>,
+    pub actual_sender: Option<String>,
+    /// Positive when shadow mode decided before the trove was liquidated on-chain
+    pub blocks_after_shadow: Option<i64>,
+}
 
-    pub async fn _get_trove_by_id(&self, trove_id: &str) -> Result<Option<Trove>> {
-        let trove = sqlx::query_as::<_, Trove>("SELECT * FROM troves WHERE trove_id = ?")
-            .bind(trove_id)
-            .fetch_optional(&self.pool)
-            .await?;
-        Ok(trove)
-    }
+/// A recorded `Liquidation` event together with its realized outcome
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LiquidationRecord {
+    pub id: i64,
+    pub branch: String,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub sender: String,
+    pub is_ours: bool,
+    pub debt_offset_by_sp: String,
+    pub debt_redistributed: String,
+    pub coll_gas_compensation: String,
+    pub bold_gas_compensation: String,
+    pub coll_sent_to_sp: String,
+    pub coll_redistributed: String,
+    pub coll_surplus: String,
+    pub price: String,
+    pub gas_used: i64,
+    pub effective_gas_price: String,
+    pub gas_cost: String,
+    pub net_profit_coll: String,
+    pub net_profit_usd: String,
+    pub gas_compensation_usd_numeric: f64,
+    pub net_profit_usd_numeric: f64,
+}
 
+/// Aggregated liquidation outcomes for a period and/or branch
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LiquidationSummary {
+    pub period: String,
+    pub branch: String,
+    pub liquidations: i64,
+    pub ours: i64,
+    pub our_gas_compensation_usd: f64,
+    pub our_net_profit_usd: f64,
+}
 
-     pub async fn upsert_trove(&self, trove: &Trove) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO troves (trove_id, collateral, debt, icr,interest_rate, icr_numeric, status, last_updated)
-            VALUES (?, ?, ?, ?, ?, ? , ? , ?)
-            ON CONFLICT(trove_id) DO UPDATE SET
-                collateral = excluded.collateral,
-                debt = excluded.debt,
-                icr = excluded.icr,
-                interest_rate = interest_rate,
-                icr_numeric = excluded.icr_numeric,
-                status = excluded.status,
-                last_updated = excluded.last_updated
-            "#,
-        )
-        .bind(&trove.trove_id)
-        .bind(&trove.collateral)
-        .bind(&trove.debt)
-        .bind(&trove.icr)
-        .bind(&trove.interest_rate)
-        .bind(trove.icr_numeric)
-        .bind(&trove.status)
-        .bind(trove.last_updated)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
-     }
-
-     pub async fn close_troves(&self, trove_ids: &Vec<Uint::<256, 4>>) -> Result<(), sqlx::Error> {
-        if trove_ids.is_empty() {
-            return Ok(());
+/// A trove our model flagged as liquidatable
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LiquidationCandidate {
+    pub trove_id: String,
+    pub first_liquidatable_block: i64,
+    pub last_seen_block: i64,
+    pub attempted: bool,
+    pub attempt_block: Option<i64>,
+    pub attempt_tx: Option<String>,
+}
+
+/// Attribution of a single liquidated trove to the transaction that took it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LiquidatedTrove {
+    pub id: i64,
+    pub trove_id: String,
+    pub tx_hash: String,
+    pub block_number: i64,
+    pub sender: String,
+    pub is_ours: bool,
+    pub effective_gas_price: String,
+    pub gas_price_gwei: f64,
+    pub liquidatable_since_block: Option<i64>,
+    pub blocks_after_eligible: Option<i64>,
+    pub we_attempted: bool,
+}
+
+/// Per-sender statistics over troves liquidated by someone else
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CompetitorStats {
+    pub sender: String,
+    pub troves: i64,
+    pub tracked: i64,
+    pub we_attempted: i64,
+    pub avg_blocks_after_eligible: Option<f64>,
+    pub min_blocks_after_eligible: Option<i64>,
+    pub avg_gas_price_gwei: f64,
+    pub max_gas_price_gwei: f64,
+}
+
+/// Lifecycle of a trove, mirroring `TroveManager.Status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TroveStatus {
+    NonExistent,
+    Active,
+    ClosedByOwner,
+    ClosedByLiquidation,
+    /// Redeemed below the minimum debt; still liquidatable
+    Zombie,
+}
+
+impl TroveStatus {
+    /// Convert the raw `TroveManager.Status` enum value
+    pub fn from_chain(status: u8) -> Self {
+        match status {
+            1 => TroveStatus::Active,
+            2 => TroveStatus::ClosedByOwner,
+            3 => TroveStatus::ClosedByLiquidation,
+            4 => TroveStatus::Zombie,
+            _ => TroveStatus::NonExistent,
         }
-    
-        // dynamically create placeholders: ?,?,? for SQLite/MySQL
-        let placeholders = std::iter::repeat("?")
-            .take(trove_ids.len())
-            .collect::<Vec<_>>()
-            .join(", ");
-    
-        let query = format!(
-            r#"
-            UPDATE troves
-            SET status = 'closed', last_updated = ?
-            WHERE trove_id IN ({})
-            AND status = 'active'
-            "#,
-            placeholders
-        );
-    
-        let mut q = sqlx::query(&query);
-    
-        // Optional: update last_updated time
-        let now = Utc::now();
-        q = q.bind(now);
-    
-        for id in trove_ids {
-            // Convert to hex string
-            let id_str = format!("0x{:x}", id);
-            q = q.bind(id_str);
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TroveStatus::NonExistent => "non_existent",
+            TroveStatus::Active => "active",
+            TroveStatus::ClosedByOwner => "closed_by_owner",
+            TroveStatus::ClosedByLiquidation => "closed_by_liquidation",
+            TroveStatus::Zombie => "zombie",
         }
-    
-        q.execute(&self.pool).await?;
-    
-        Ok(())
     }
-    
-
-    pub async fn _delete_trove(&self, trove_id: &str) -> Result<()> {
-        sqlx::query("DELETE FROM troves WHERE trove_id = ?")
-            .bind(trove_id)
-            .execute(&self.pool)
-            .await?;
-        Ok(())
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "non_existent" => Some(TroveStatus::NonExistent),
+            "active" => Some(TroveStatus::Active),
+            "closed_by_owner" => Some(TroveStatus::ClosedByOwner),
+            "closed_by_liquidation" => Some(TroveStatus::ClosedByLiquidation),
+            "zombie" => Some(TroveStatus::Zombie),
+            _ => None,
+        }
     }
 
+    /// Whether the trove is still open and can be liquidated
+    pub fn is_open(&self) -> bool {
+        matches!(self, TroveStatus::Active | TroveStatus::Zombie)
+    }
 
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TroveStatus::ClosedByOwner | TroveStatus::ClosedByLiquidation)
+    }
+}
 
-    // ========== User Collateral Methods ==========
+/// Persistence for checkpoints, troves, events and liquidation records. SQLite is the default
+/// backend; `initialize_database` picks PostgreSQL for `postgres://` URLs.
+#[async_trait::async_trait]
+pub trait DatabaseStore: Send + Sync {
+    /// Round-trip a trivial query to confirm the database is reachable
+    async fn ping(&self) -> Result<()>;
 
-    /// Fetch all collateral for a specific user
-   
+    async fn get_last_block(&self) -> Result<i64>;
 
-    // ========== Utility Methods ==========
+    async fn set_last_block(&self, block_number: i64) -> Result<()>;
 
-    /// Get all users with collateral or debt
-    /// Get users with both collateral and debt (potential liquidation candidates)
-    pub async fn _get_users_with_positions(&self) -> Result<Vec<String>> {
-        let users = sqlx::query(
-            r#"
-            SELECT DISTINCT c.user_address
-            FROM user_collateral c
-            INNER JOIN user_debt d ON c.user_address = d.user_address
-            ORDER BY c.user_address
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    // ========== Troves Table Methods ==========
 
-        let user_addresses: Vec<String> =
-            users.into_iter().map(|row| row.get::<String, _>("user_address")).collect();
+    /// The `limit` open troves with the lowest stored ICR, lowest first
+    async fn get_lowest_icr_troves(&self, limit: i64) -> Result<Vec<Trove>>;
 
-        Ok(user_addresses)
-    }
+    /// Open troves in redemption order (lowest annual interest rate first)
+    async fn get_troves_by_interest_rate(&self, limit: i64) -> Result<Vec<Trove>>;
+
+    /// Open troves with the largest recorded debt first
+    async fn get_troves_by_debt(&self, limit: i64) -> Result<Vec<Trove>>;
+
+    /// Every active or zombie trove
+    async fn get_open_troves(&self) -> Result<Vec<Trove>>;
+
+    /// Page through the troves the reconciler checks, ordered by id, starting after `cursor`
+    /// (exclusive): open troves, plus troves closed locally before our liquidation was sent
+    /// whose debt has not yet been zeroed by the on-chain liquidation
+    async fn get_reconcilable_troves_after(&self, cursor: &str, limit: i64) -> Result<Vec<Trove>>;
+
+    async fn get_trove_by_id(&self, trove_id: &str) -> Result<Option<Trove>>;
+
+    async fn upsert_trove(&self, trove: &Trove) -> Result<()>;
+
+    async fn set_liquidation_price(
+        &self,
+        trove_id: &str,
+        last_updated_at: i64,
+        liquidation_price: U256,
+        liquidation_price_until: i64,
+    ) -> Result<()>;
+
+    async fn set_trove_status(
+        &self,
+        trove_id: &str,
+        status: TroveStatus,
+        block_number: i64,
+    ) -> Result<()>;
+
+    /// Mark open troves as liquidated at `block_number`
+    async fn close_troves(&self, trove_ids: &[Uint<256, 4>], block_number: i64) -> Result<()>;
+
+    // ========== Trove Events Table Methods ==========
+
+    /// Record a trove operation; replaying the same log is a no-op
+    async fn insert_trove_event(&self, event: &TroveEventInsert) -> Result<()>;
+
+    /// Fetch every operation recorded for a trove, oldest first
+    async fn get_trove_timeline(&self, trove_id: &str) -> Result<Vec<TroveEvent>>;
+
+    /// Fetch the operations recorded for a trove within a block range (inclusive), oldest first
+    async fn get_trove_timeline_between(
+        &self,
+        trove_id: &str,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<Vec<TroveEvent>>;
+
+    /// Fetch the most recent operation recorded for a trove
+    async fn get_last_trove_event(&self, trove_id: &str) -> Result<Option<TroveEvent>>;
+
+    // ========== Liquidations Table Methods ==========
+
+    /// Record a liquidation; replaying the same log is a no-op
+    async fn insert_liquidation(&self, record: &LiquidationInsert) -> Result<()>;
+
+    async fn get_liquidations_by_tx(&self, tx_hash: &str) -> Result<Vec<LiquidationRecord>>;
+
+    /// Daily liquidation outcomes per branch, most recent day first
+    async fn get_liquidation_summary_by_day(&self) -> Result<Vec<LiquidationSummary>>;
+
+    /// All-time liquidation outcomes per branch
+    async fn get_liquidation_summary_by_branch(&self) -> Result<Vec<LiquidationSummary>>;
+
+    // ========== Competitor Analytics Methods ==========
+
+    /// Record troves our model found liquidatable at this block, keeping the first sighting
+    async fn record_liquidation_candidates(
+        &self,
+        trove_ids: &[Uint<256, 4>],
+        block_number: i64,
+    ) -> Result<()>;
+
+    /// Mark candidates as attempted by one of our liquidation transactions
+    async fn mark_candidates_attempted(
+        &self,
+        trove_ids: &[Uint<256, 4>],
+        block_number: i64,
+        tx_hash: Option<&str>,
+    ) -> Result<()>;
+
+    async fn get_liquidation_candidate(
+        &self,
+        trove_id: &str,
+    ) -> Result<Option<LiquidationCandidate>>;
+
+    /// Trove ids liquidated in a transaction, according to its TroveOperation events
+    async fn get_troves_liquidated_in_tx(&self, tx_hash: &str) -> Result<Vec<String>>;
+
+    async fn insert_liquidated_trove(&self, trove: &LiquidatedTroveInsert) -> Result<()>;
+
+    /// Troves liquidated by others, most recent first
+    async fn get_lost_liquidations(&self, limit: i64) -> Result<Vec<LiquidatedTrove>>;
+
+    /// Per-sender race statistics for troves liquidated by others
+    async fn get_competitor_stats(&self) -> Result<Vec<CompetitorStats>>;
+
+    // ========== Shadow Liquidation Methods ==========
+
+    /// Record a liquidation shadow mode would have sent, keeping the block it was first decided
+    async fn record_shadow_liquidation(&self, record: &ShadowLiquidationInsert) -> Result<()>;
+
+    /// Whether shadow mode already decided to liquidate this trove
+    async fn has_shadow_liquidation(&self, trove_id: &str) -> Result<bool>;
+
+    /// Shadow decisions next to the on-chain liquidation of the same trove, most recent first
+    async fn get_shadow_comparison(&self, limit: i64) -> Result<Vec<ShadowComparison>>;
 }
 
 /// Struct for inserting new asset configurations
@@ -257,3 +432,76 @@ pub struct AssetConfigInsert {
     pub variable_debt_token_address: String,
     pub interest_rate_strategy_address: String,
 }
+
+/// Struct for inserting new trove events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TroveEventInsert {
+    pub trove_id: String,
+    pub block_number: i64,
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub operation: String,
+    pub annual_interest_rate: String,
+    pub debt_increase_from_redist: String,
+    pub debt_increase_from_upfront_fee: String,
+    pub debt_change_from_operation: String,
+    pub coll_increase_from_redist: String,
+    pub coll_change_from_operation: String,
+}
+
+/// Struct for inserting new liquidation records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationInsert {
+    pub branch: String,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub sender: String,
+    pub is_ours: bool,
+    pub debt_offset_by_sp: String,
+    pub debt_redistributed: String,
+    pub coll_gas_compensation: String,
+    pub bold_gas_compensation: String,
+    pub coll_sent_to_sp: String,
+    pub coll_redistributed: String,
+    pub coll_surplus: String,
+    pub price: String,
+    pub gas_used: i64,
+    pub effective_gas_price: String,
+    pub gas_cost: String,
+    pub net_profit_coll: String,
+    pub net_profit_usd: String,
+    pub gas_compensation_usd_numeric: f64,
+    pub net_profit_usd_numeric: f64,
+}
+
+/// Struct for inserting liquidated trove attributions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidatedTroveInsert {
+    pub trove_id: String,
+    pub tx_hash: String,
+    pub block_number: i64,
+    pub sender: String,
+    pub is_ours: bool,
+    pub effective_gas_price: String,
+    pub gas_price_gwei: f64,
+    pub liquidatable_since_block: Option<i64>,
+    pub blocks_after_eligible: Option<i64>,
+    pub we_attempted: bool,
+}
+
+/// Struct for recording a shadow-mode liquidation of one trove
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowLiquidationInsert {
+    pub trove_id: String,
+    pub block_number: i64,
+    /// Troves in the batch this trove would have been liquidated with
+    pub batch_size: i64,
+    pub estimated_gas: Option<i64>,
+    pub gas_price: String,
+    pub expected_coll_compensation: String,
+    pub expected_eth_compensation: String,
+    pub simulated_ok: bool,
+    pub error: Option<String>,
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    Decode, Encode, Postgres, Sqlite, Type,
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef},
+};
+
+/// A U256 column stored as a 32-byte big-endian BLOB (BYTEA in PostgreSQL). Both compare these
+/// bytewise, so `ORDER BY` and range queries on these columns follow numeric order without any
+/// loss.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StoredU256(pub U256);
+
+impl StoredU256 {
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BoxDynError> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| format!("expected a 32-byte U256, got {} bytes", bytes.len()))?;
+        Ok(Self(U256::from_be_bytes(bytes)))
+    }
+}
+
+impl From<U256> for StoredU256 {
+    fn from(value: U256) -> Self {
+        Self(value)
+    }
+}
+
+impl From<StoredU256> for U256 {
+    fn from(value: StoredU256) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for StoredU256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Type<Sqlite> for StoredU256 {
+    fn type_info() -> SqliteTypeInfo {
+        <Vec<u8> as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <Vec<u8> as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for StoredU256 {
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<IsNull, BoxDynError> {
+        <Vec<u8> as Encode<'q, Sqlite>>::encode(self.to_bytes().to_vec(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for StoredU256 {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        Self::from_bytes(<&[u8] as Decode<'r, Sqlite>>::decode(value)?)
+    }
+}
+
+impl Type<Postgres> for StoredU256 {
+    fn type_info() -> PgTypeInfo {
+        <Vec<u8> as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Vec<u8> as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for StoredU256 {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <&[u8] as Encode<'_, Postgres>>::encode(self.to_bytes().as_slice(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for StoredU256 {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Self::from_bytes(<&[u8] as Decode<'r, Postgres>>::decode(value)?)
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use alloy::{
+    rpc::json_rpc::{RequestPacket, ResponsePacket},
+    transports::{
+        TransportError, TransportFut,
+        http::{Http, reqwest},
+    },
+};
+use eyre::Result;
+use log::warn;
+use tower::Service;
+
+use crate::notifier::{Alert, AlertKind, Notifier};
+
+/// HTTP transport over a primary RPC and its backups. Requests go to the active endpoint; when it
+/// fails at the transport level the next one is tried, and the first that answers becomes active.
+/// JSON-RPC error responses come back as successes and never cause a switch.
+///
+/// Clones share the active endpoint, so one failover applies to every client built from it.
+#[derive(Clone)]
+pub struct FailoverTransport {
+    transports: Arc<Vec<Http<reqwest::Client>>>,
+    urls: Arc<Vec<String>>,
+    active: Arc<AtomicUsize>,
+    notifier: Notifier,
+}
+
+impl FailoverTransport {
+    pub fn new(primary: &str, fallbacks: &[&str], notifier: Notifier) -> Result<Self> {
+        let urls: Vec<String> =
+            std::iter::once(primary).chain(fallbacks.iter().copied()).map(String::from).collect();
+        let transports = urls
+            .iter()
+            .map(|url| Ok(Http::new(url.parse()?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            transports: Arc::new(transports),
+            urls: Arc::new(urls),
+            active: Arc::new(AtomicUsize::new(0)),
+            notifier,
+        })
+    }
+}
+
+impl Service<RequestPacket> for FailoverTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            let start = this.active.load(Ordering::Acquire);
+            let count = this.transports.len();
+            let mut last_error = None;
+
+            for attempt in 0..count {
+                let index = (start + attempt) % count;
+                let mut transport = this.transports[index].clone();
+                match transport.call(request.clone()).await {
+                    Ok(response) => {
+                        if index != start
+                            && this
+                                .active
+                                .compare_exchange(start, index, Ordering::AcqRel, Ordering::Acquire)
+                                .is_ok()
+                        {
+                            let (from, to) = (&this.urls[start], &this.urls[index]);
+                            warn!("🔀 RPC failover: {} -> {}", from, to);
+                            this.notifier
+                                .notify(
+                                    Alert::new(
+                                        AlertKind::RpcFailover,
+                                        format!("rpc_failover:{}", index),
+                                        "RPC failover",
+                                    )
+                                    .field("from", from)
+                                    .field("to", to)
+                                    .field(
+                                        "error",
+                                        last_error
+                                            .as_ref()
+                                            .map(|e: &TransportError| e.to_string())
+                                            .unwrap_or_default(),
+                                    ),
+                                )
+                                .await;
+                        }
+                        return Ok(response);
+                    }
+                    Err(e) => {
+                        warn!("⚠️ RPC request to {} failed: {}", this.urls[index], e);
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            Err(last_error.expect("at least one RPC endpoint"))
+        })
+    }
+}
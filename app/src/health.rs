@@ -0,0 +1,251 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
+use log::error;
+use serde::Serialize;
+use tokio::time::{sleep, timeout};
+
+use crate::{
+    db::DatabaseStore,
+    liquity::liquity_strategy::StrategyProvider,
+    metrics,
+    notifier::{Alert, AlertKind, Notifier},
+};
+
+/// Upper bound on each live probe made by `/readyz`
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+static BACKFILL_COMPLETE: AtomicBool = AtomicBool::new(false);
+static LAST_HEARTBEAT: AtomicU64 = AtomicU64::new(0);
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Mark the history backfill as finished
+pub fn set_backfill_complete() {
+    BACKFILL_COMPLETE.store(true, Ordering::Release);
+}
+
+/// Record that a collector loop made progress
+pub fn heartbeat() {
+    LAST_HEARTBEAT.store(now_secs(), Ordering::Release);
+}
+
+/// Thresholds the readiness probe is evaluated against
+#[derive(Debug, Clone)]
+pub struct HealthThresholds {
+    pub max_block_lag: u64,
+    pub max_oracle_staleness_secs: u64,
+    pub min_signer_balance: U256,
+    /// `/healthz` fails once no collector has made progress for this long
+    pub max_stall_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessReport {
+    pub alive: bool,
+    pub secs_since_heartbeat: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub backfill_complete: bool,
+    pub head_block: Option<u64>,
+    pub processed_block: u64,
+    pub block_lag: Option<u64>,
+    pub secs_since_oracle_read: Option<u64>,
+    pub signer_balance: Option<String>,
+    pub db_reachable: bool,
+    /// Human-readable reason for every failed check
+    pub failures: Vec<String>,
+}
+
+/// Evaluates liveness and readiness for the `/healthz` and `/readyz` endpoints
+#[derive(Clone)]
+pub struct HealthChecker {
+    store: Arc<dyn DatabaseStore>,
+    provider: Arc<StrategyProvider>,
+    signer: Address,
+    thresholds: HealthThresholds,
+}
+
+impl HealthChecker {
+    pub fn new(
+        store: Arc<dyn DatabaseStore>,
+        provider: Arc<StrategyProvider>,
+        signer: Address,
+        thresholds: HealthThresholds,
+    ) -> Self {
+        Self { store, provider, signer, thresholds }
+    }
+
+    /// Alive while some collector loop has made progress recently. Before the first heartbeat
+    /// the process is still starting up and counts as alive.
+    pub fn liveness(&self) -> LivenessReport {
+        let last = LAST_HEARTBEAT.load(Ordering::Acquire);
+        if last == 0 {
+            return LivenessReport { alive: true, secs_since_heartbeat: None };
+        }
+
+        let secs = now_secs().saturating_sub(last);
+        LivenessReport { alive: secs <= self.thresholds.max_stall_secs, secs_since_heartbeat: Some(secs) }
+    }
+
+    pub async fn readiness(&self) -> ReadinessReport {
+        let mut failures = Vec::new();
+
+        let backfill_complete = BACKFILL_COMPLETE.load(Ordering::Acquire);
+        if !backfill_complete {
+            failures.push("history backfill in progress".to_string());
+        }
+
+        // Head is read live so a stalled block loop cannot hide its own lag
+        let processed_block = metrics::PROCESSED_BLOCK.get().max(0) as u64;
+        let head_block = match timeout(PROBE_TIMEOUT, self.provider.get_block_number()).await {
+            Ok(Ok(head)) => Some(head),
+            Ok(Err(e)) => {
+                failures.push(format!("head block unavailable: {}", e));
+                None
+            }
+            Err(_) => {
+                failures.push("head block request timed out".to_string());
+                None
+            }
+        };
+        let block_lag = head_block.map(|head| head.saturating_sub(processed_block));
+        if let Some(lag) = block_lag.filter(|lag| *lag > self.thresholds.max_block_lag) {
+            failures.push(format!(
+                "processed block {} is {} blocks behind head (max {})",
+                processed_block, lag, self.thresholds.max_block_lag
+            ));
+        }
+
+        let last_oracle_read = metrics::ORACLE_LAST_READ.get();
+        let secs_since_oracle_read =
+            (last_oracle_read > 0).then(|| now_secs().saturating_sub(last_oracle_read as u64));
+        match secs_since_oracle_read {
+            Some(secs) if secs > self.thresholds.max_oracle_staleness_secs => failures.push(
+                format!(
+                    "last oracle read {}s ago (max {}s)",
+                    secs, self.thresholds.max_oracle_staleness_secs
+                ),
+            ),
+            Some(_) => {}
+            None => failures.push("oracle not read yet".to_string()),
+        }
+
+        let signer_balance = match timeout(PROBE_TIMEOUT, self.provider.get_balance(self.signer)).await
+        {
+            Ok(Ok(balance)) => Some(balance),
+            Ok(Err(e)) => {
+                failures.push(format!("signer balance unavailable: {}", e));
+                None
+            }
+            Err(_) => {
+                failures.push("signer balance request timed out".to_string());
+                None
+            }
+        };
+        if let Some(balance) =
+            signer_balance.filter(|balance| *balance < self.thresholds.min_signer_balance)
+        {
+            failures.push(format!(
+                "signer {:?} balance {} below {}",
+                self.signer, balance, self.thresholds.min_signer_balance
+            ));
+        }
+
+        let db_reachable = matches!(timeout(PROBE_TIMEOUT, self.store.ping()).await, Ok(Ok(())));
+        if !db_reachable {
+            failures.push("database unreachable".to_string());
+        }
+
+        ReadinessReport {
+            ready: failures.is_empty(),
+            backfill_complete,
+            head_block,
+            processed_block,
+            block_lag,
+            secs_since_oracle_read,
+            signer_balance: signer_balance.map(|balance| balance.to_string()),
+            db_reachable,
+            failures,
+        }
+    }
+
+    /// Periodically alert on a low signer balance or a stale oracle until the process exits
+    pub async fn watch(&self, notifier: Notifier, interval: Duration) {
+        loop {
+            sleep(interval).await;
+
+            match timeout(PROBE_TIMEOUT, self.provider.get_balance(self.signer)).await {
+                Ok(Ok(balance)) if balance < self.thresholds.min_signer_balance => {
+                    notifier
+                        .notify(
+                            Alert::new(
+                                AlertKind::BalanceLow,
+                                format!("balance_low:{:?}", self.signer),
+                                "Executor wallet balance low",
+                            )
+                            .field("signer", format!("{:?}", self.signer))
+                            .field("balance_wei", balance)
+                            .field("minimum_wei", self.thresholds.min_signer_balance),
+                        )
+                        .await;
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("❌ Watchdog failed to read signer balance: {}", e),
+                Err(_) => error!("❌ Watchdog timed out reading signer balance"),
+            }
+
+            let last_read = metrics::ORACLE_LAST_READ.get();
+            if last_read == 0 {
+                continue;
+            }
+            let secs_since_read = now_secs().saturating_sub(last_read as u64);
+            let max_staleness = self.thresholds.max_oracle_staleness_secs;
+            if secs_since_read > max_staleness {
+                notifier
+                    .notify(
+                        Alert::new(AlertKind::OracleStale, "oracle_stale", "Oracle price is stale")
+                            .field("secs_since_read", secs_since_read)
+                            .field("answer_age_secs", metrics::ORACLE_AGE.get())
+                            .field("max_staleness_secs", max_staleness),
+                    )
+                    .await;
+            }
+        }
+    }
+}
+
+async fn healthz_handler(State(checker): State<HealthChecker>) -> impl IntoResponse {
+    let report = checker.liveness();
+    let status = if report.alive { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+async fn readyz_handler(State(checker): State<HealthChecker>) -> impl IntoResponse {
+    let report = checker.readiness().await;
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+/// `/healthz` and `/readyz` routes
+pub fn router(checker: HealthChecker) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz_handler))
+        .route("/readyz", get(readyz_handler))
+        .with_state(checker)
+}
@@ -0,0 +1,51 @@
+pub mod collector;
+pub mod config;
+pub mod db;
+pub mod failover;
+pub mod health;
+pub mod liquity;
+pub mod metrics;
+pub mod multicall;
+pub mod notifier;
+pub mod replay;
+pub mod strategy;
+#[cfg(test)]
+mod test;
+
+use alloy::{
+    network::EthereumWallet,
+    providers::{
+        Identity, RootProvider,
+        fillers::{
+            BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller,
+            WalletFiller,
+        },
+    },
+    sol,
+};
+use serde::{Deserialize, Serialize};
+
+pub type DefaultProvider = FillProvider<
+    JoinFill<
+        JoinFill<
+            Identity,
+            JoinFill<GasFiller, JoinFill<BlobGasFiller, JoinFill<NonceFiller, ChainIdFiller>>>,
+        >,
+        WalletFiller<EthereumWallet>,
+    >,
+    RootProvider,
+>;
+
+sol!(
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[sol(rpc)]
+    AddressRegistry,
+    "../artifacts/AddressRegistry.sol/AddressesRegistry.json"
+);
+
+sol!(
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[sol(rpc)]
+    PriceFeed,
+    "../artifacts/IPriceFeed.sol/IPriceFeed.json"
+);
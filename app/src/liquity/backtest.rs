@@ -0,0 +1,441 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use alloy::{
+    primitives::{I256, U256},
+    rpc::types::Log,
+};
+use eyre::Result;
+use log::info;
+use serde::Deserialize;
+
+use crate::{
+    config::CandidateSource,
+    db::store::{Trove, TroveStatus},
+    liquity::{
+        liquidation_recorder::{liquidation_profit, units_to_f64},
+        liquity::{TroveManager::TroveManagerEvents, decode_event_log, operation},
+        liquity_exexcution::{ETH_GAS_COMPENSATION, coll_gas_compensation},
+        liquity_strategy::{
+            LiquityStrategy, MAX_ICR_CANDIDATES, TroveScan, scan_troves,
+        },
+        trove_memory_cache::TroveIndex,
+    },
+};
+
+/// One line of a recorded stream. Prices are 18-decimal USD: `price` is the branch oracle and
+/// `native_price` the gas token, which also values the WETH gas compensation. A log's block
+/// timestamp comes from `timestamp` or, failing that, the log's own `blockTimestamp`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BacktestRecord {
+    Log { timestamp: Option<u64>, log: Log },
+    Price { block: u64, timestamp: u64, price: U256 },
+    NativePrice { block: u64, timestamp: u64, price: U256 },
+}
+
+impl BacktestRecord {
+    fn block(&self) -> u64 {
+        match self {
+            BacktestRecord::Log { log, .. } => log.block_number.unwrap_or_default(),
+            BacktestRecord::Price { block, .. } | BacktestRecord::NativePrice { block, .. } => {
+                *block
+            }
+        }
+    }
+}
+
+/// Read a JSON-lines stream, ordered by block with the file order kept inside a block
+pub fn load_records(path: &Path) -> Result<Vec<BacktestRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: BacktestRecord = serde_json::from_str(&line)
+            .map_err(|e| eyre::eyre!("{}:{}: {}", path.display(), line_number + 1, e))?;
+        records.push(record);
+    }
+    records.sort_by_key(BacktestRecord::block);
+    Ok(records)
+}
+
+/// Stands in for `LiquityExecutor`: every batch succeeds at a fixed gas model
+#[derive(Debug, Clone)]
+pub struct SimulatedExecutor {
+    pub gas_per_batch: u64,
+    pub gas_per_trove: u64,
+    pub gas_price: u128,
+}
+
+impl SimulatedExecutor {
+    /// Gas one trove is charged when liquidated in a batch of `batch_size`
+    fn gas_per_liquidation(&self, batch_size: usize) -> u64 {
+        self.gas_per_batch / batch_size.max(1) as u64 + self.gas_per_trove
+    }
+
+    /// Compensation minus gas (USD, 18 decimals), priced like `LiquidationRecorder` prices
+    /// real liquidations
+    fn profit(&self, trove: &Trove, batch_size: usize, price: U256, native_price: U256) -> I256 {
+        let gas_cost =
+            U256::from(self.gas_per_liquidation(batch_size)) * U256::from(self.gas_price);
+        liquidation_profit(
+            coll_gas_compensation(trove.collateral()),
+            U256::from(ETH_GAS_COMPENSATION),
+            gas_cost,
+            price,
+            native_price,
+        )
+        .net_profit_usd
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub mcr: U256,
+    pub candidate_source: CandidateSource,
+    pub liquidation_price_horizon_secs: u64,
+    pub executor: SimulatedExecutor,
+}
+
+/// A liquidation the strategy would have sent
+#[derive(Debug, Clone)]
+pub struct BacktestLiquidation {
+    pub trove_id: String,
+    /// First block the ICR model put the trove under MCR
+    pub eligible_block: u64,
+    pub found_block: u64,
+    /// Block the trove was actually liquidated on-chain, if the stream shows it
+    pub actual_block: Option<u64>,
+    pub gas_used: u64,
+    pub profit_usd: f64,
+}
+
+impl BacktestLiquidation {
+    pub fn blocks_after_eligible(&self) -> u64 {
+        self.found_block - self.eligible_block
+    }
+}
+
+/// An on-chain liquidation the strategy never found
+#[derive(Debug, Clone)]
+pub struct MissedLiquidation {
+    pub trove_id: String,
+    /// `None` when the ICR model never saw the trove under MCR
+    pub eligible_block: Option<u64>,
+    pub actual_block: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub blocks: usize,
+    pub logs: usize,
+    pub price_updates: usize,
+    pub liquidations: Vec<BacktestLiquidation>,
+    pub missed: Vec<MissedLiquidation>,
+}
+
+impl BacktestReport {
+    pub fn total_profit_usd(&self) -> f64 {
+        self.liquidations.iter().map(|liquidation| liquidation.profit_usd).sum()
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Replayed {} blocks ({} logs, {} price updates)",
+            self.blocks, self.logs, self.price_updates
+        );
+        println!(
+            "{:<78} {:>10} {:>10} {:>6} {:>10} {:>10} {:>12}",
+            "trove", "eligible", "found", "delay", "actual", "gas", "profit $"
+        );
+        for liquidation in &self.liquidations {
+            println!(
+                "{:<78} {:>10} {:>10} {:>6} {:>10} {:>10} {:>12.2}",
+                liquidation.trove_id,
+                liquidation.eligible_block,
+                liquidation.found_block,
+                liquidation.blocks_after_eligible(),
+                liquidation.actual_block.map_or("-".to_string(), |block| block.to_string()),
+                liquidation.gas_used,
+                liquidation.profit_usd
+            );
+        }
+        for missed in &self.missed {
+            println!(
+                "{:<78} {:>10} {:>10} {:>6} {:>10}  missed",
+                missed.trove_id,
+                missed.eligible_block.map_or("-".to_string(), |block| block.to_string()),
+                "-",
+                "-",
+                missed.actual_block
+            );
+        }
+
+        let delays: Vec<u64> =
+            self.liquidations.iter().map(BacktestLiquidation::blocks_after_eligible).collect();
+        let average_delay = if delays.is_empty() {
+            0.0
+        } else {
+            delays.iter().sum::<u64>() as f64 / delays.len() as f64
+        };
+        println!(
+            "Found {} liquidations (avg {:.2} blocks after eligibility), missed {}, \
+             modeled profit ${:.2}",
+            self.liquidations.len(),
+            average_delay,
+            self.missed.len(),
+            self.total_profit_usd()
+        );
+    }
+}
+
+/// Replays a recorded stream through the strategy's ICR scan and candidate ordering against an
+/// in-memory trove book. Interest re-check scheduling and zombie transitions are not modeled,
+/// nor is the SortedTroves list: that source is approximated by walking up from the lowest rate.
+/// A stream without `native_price` records is taken to be from the WETH branch.
+pub struct Backtester {
+    config: BacktestConfig,
+    troves: HashMap<String, Trove>,
+    /// Same ordered index the strategy's trove book walks
+    index: TroveIndex,
+    /// First block each trove was under MCR for the ICR model
+    eligible: HashMap<String, u64>,
+    /// Troves the simulated executor already liquidated, with their index in the report
+    ours: HashMap<String, usize>,
+    /// Troves closed during the current block; a liquidation's `TroveUpdated` zeroes the trove
+    /// before its `TroveOperation` says how it was closed
+    closed: HashSet<String>,
+    price: Option<U256>,
+    native_price: Option<U256>,
+    block_timestamp: u64,
+    report: BacktestReport,
+}
+
+impl Backtester {
+    pub fn new(config: BacktestConfig) -> Self {
+        Self {
+            config,
+            troves: HashMap::new(),
+            index: TroveIndex::default(),
+            eligible: HashMap::new(),
+            ours: HashMap::new(),
+            closed: HashSet::new(),
+            price: None,
+            native_price: None,
+            block_timestamp: 0,
+            report: BacktestReport::default(),
+        }
+    }
+
+    pub fn run(mut self, records: &[BacktestRecord]) -> Result<BacktestReport> {
+        let mut index = 0;
+        while index < records.len() {
+            let block_number = records[index].block();
+            while index < records.len() && records[index].block() == block_number {
+                self.apply(&records[index])?;
+                index += 1;
+            }
+            self.check_block(block_number);
+            for trove_id in std::mem::take(&mut self.closed) {
+                self.eligible.remove(&trove_id);
+            }
+            self.report.blocks += 1;
+        }
+
+        info!(
+            "🧪 Backtest finished - {} liquidations found, {} missed",
+            self.report.liquidations.len(),
+            self.report.missed.len()
+        );
+        Ok(self.report)
+    }
+
+    fn apply(&mut self, record: &BacktestRecord) -> Result<()> {
+        match record {
+            BacktestRecord::Price { timestamp, price, .. } => {
+                self.report.price_updates += 1;
+                self.price = Some(*price);
+                self.block_timestamp = self.block_timestamp.max(*timestamp);
+            }
+            BacktestRecord::NativePrice { timestamp, price, .. } => {
+                self.report.price_updates += 1;
+                self.native_price = Some(*price);
+                self.block_timestamp = self.block_timestamp.max(*timestamp);
+            }
+            BacktestRecord::Log { timestamp, log } => {
+                self.report.logs += 1;
+                let timestamp = timestamp.or(log.block_timestamp).ok_or_else(|| {
+                    eyre::eyre!("Log at block {:?} has no timestamp", log.block_number)
+                })?;
+                self.block_timestamp = self.block_timestamp.max(timestamp);
+                if let Some(event) = decode_event_log(log) {
+                    self.apply_event(&event, log.block_number.unwrap_or_default(), timestamp);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_event(&mut self, event: &TroveManagerEvents, block_number: u64, timestamp: u64) {
+        match event {
+            TroveManagerEvents::TroveUpdated(event) => {
+                let trove_id = event._troveId.to_string();
+                if self.ours.contains_key(&trove_id) {
+                    return;
+                }
+                let existing_status = self
+                    .troves
+                    .get(&trove_id)
+                    .and_then(|trove| TroveStatus::parse(&trove.status));
+                let status =
+                    LiquityStrategy::updated_status(event._coll, event._debt, existing_status);
+                if !status.is_open() {
+                    self.close(&trove_id);
+                    return;
+                }
+
+                let mut trove = Trove {
+                    trove_id: trove_id.clone(),
+                    collateral: event._coll.into(),
+                    debt: event._debt.into(),
+                    icr_key: Trove::icr_key(event._coll, event._debt),
+                    interest_rate: event._annualInterestRate.into(),
+                    status: status.as_str().to_string(),
+                    last_updated: block_number as i64,
+                    last_updated_at: timestamp as i64,
+                    liquidation_price: U256::ZERO.into(),
+                    liquidation_price_until: 0,
+                };
+                self.refresh_liquidation_price(&mut trove, timestamp);
+                self.insert(trove);
+            }
+            TroveManagerEvents::TroveOperation(event) => {
+                let trove_id = event._troveId.to_string();
+                match event._operation {
+                    operation::LIQUIDATE => self.liquidated_on_chain(&trove_id, block_number),
+                    operation::CLOSE_TROVE => self.close(&trove_id),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn insert(&mut self, trove: Trove) {
+        self.remove(&trove.trove_id);
+        self.index.insert(&trove);
+        self.troves.insert(trove.trove_id.clone(), trove);
+    }
+
+    fn remove(&mut self, trove_id: &str) -> Option<Trove> {
+        let trove = self.troves.remove(trove_id)?;
+        self.index.remove(&trove);
+        Some(trove)
+    }
+
+    fn close(&mut self, trove_id: &str) {
+        if self.remove(trove_id).is_some() {
+            self.closed.insert(trove_id.to_string());
+        }
+    }
+
+    fn liquidated_on_chain(&mut self, trove_id: &str, block_number: u64) {
+        if let Some(&index) = self.ours.get(trove_id) {
+            let liquidation = &mut self.report.liquidations[index];
+            liquidation.actual_block.get_or_insert(block_number);
+            return;
+        }
+
+        if self.troves.contains_key(trove_id) || self.closed.contains(trove_id) {
+            self.report.missed.push(MissedLiquidation {
+                trove_id: trove_id.to_string(),
+                eligible_block: self.eligible.get(trove_id).copied(),
+                actual_block: block_number,
+            });
+        }
+        self.close(trove_id);
+    }
+
+    /// Same horizon-based liquidation price the strategy keeps in its price index
+    fn refresh_liquidation_price(&self, trove: &mut Trove, now: u64) {
+        let until = now + self.config.liquidation_price_horizon_secs;
+        let liquidation_price = LiquityStrategy::calculate_liquidation_price(
+            trove.debt(),
+            trove.collateral(),
+            trove.interest_rate(),
+            self.config.mcr,
+            until.saturating_sub(trove.last_updated_at as u64),
+        );
+        trove.liquidation_price = liquidation_price.into();
+        trove.liquidation_price_until = until as i64;
+    }
+
+    /// Candidates in the order the configured source would produce them
+    fn candidates(&self, price: U256) -> (Vec<Trove>, bool) {
+        let (ids, sorted_by_icr) = match self.config.candidate_source {
+            CandidateSource::PriceIndex => (self.index.above_liquidation_price(price), false),
+            CandidateSource::SortedTroves => {
+                let mut troves: Vec<&Trove> = self.troves.values().collect();
+                troves.sort_by_key(|trove| (trove.interest_rate, trove.trove_id.clone()));
+                (troves.into_iter().map(|trove| trove.trove_id.clone()).collect(), false)
+            }
+            CandidateSource::Database => (self.index.lowest_icr(MAX_ICR_CANDIDATES), true),
+        };
+        let troves = ids.iter().filter_map(|id| self.troves.get(id).cloned()).collect();
+        (troves, sorted_by_icr)
+    }
+
+    fn check_block(&mut self, block_number: u64) {
+        let Some(price) = self.price else {
+            return;
+        };
+        let now = self.block_timestamp;
+
+        for trove_id in self.index.expired_liquidation_prices(now as i64) {
+            if let Some(mut trove) = self.remove(&trove_id) {
+                self.refresh_liquidation_price(&mut trove, now);
+                self.insert(trove);
+            }
+        }
+
+        // Ground truth for eligibility: every open trove under the ICR model
+        for trove in self.troves.values() {
+            let icr = LiquityStrategy::trove_icr(trove, now, price);
+            if icr.is_some_and(|icr| icr < self.config.mcr) {
+                self.eligible.entry(trove.trove_id.clone()).or_insert(block_number);
+            }
+        }
+
+        let (candidates, sorted_by_icr) = self.candidates(price);
+        let TroveScan { liquidatable, .. } =
+            scan_troves(&candidates, now, price, self.config.mcr, sorted_by_icr);
+        if liquidatable.is_empty() {
+            return;
+        }
+
+        let native_price = self.native_price.unwrap_or(price);
+        let batch_size = liquidatable.len();
+        for trove_id in liquidatable.iter().map(|id| id.to_string()) {
+            let Some(trove) = self.remove(&trove_id) else {
+                continue;
+            };
+            let profit = self.config.executor.profit(&trove, batch_size, price, native_price);
+            self.ours.insert(trove_id.clone(), self.report.liquidations.len());
+            self.report.liquidations.push(BacktestLiquidation {
+                eligible_block: self.eligible.remove(&trove_id).unwrap_or(block_number),
+                trove_id,
+                found_block: block_number,
+                actual_block: None,
+                gas_used: self.config.executor.gas_per_liquidation(batch_size),
+                profit_usd: units_to_f64(&profit.to_string()),
+            });
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use alloy::primitives::{Address, U256};
+use eyre::Result;
+use log::{info, warn};
+use tokio::sync::RwLock;
+
+use crate::{
+    liquity::liquity_strategy::{AggregatePriceFeed, StrategyProvider, TroveManager},
+    strategy::Strategy,
+};
+
+/// How strategies on a branch should behave
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BranchMode {
+    #[default]
+    Normal,
+    /// The branch has been shut down; only urgent redemptions are possible
+    UrgentRedemption,
+}
+
+/// Latest view of a branch's collateralization and shutdown state
+#[derive(Debug, Clone, Default)]
+pub struct BranchHealth {
+    pub total_coll: U256,
+    pub total_debt: U256,
+    pub price: U256,
+    /// Total collateral ratio (18 decimals)
+    pub tcr: U256,
+    pub scr: U256,
+    pub shutdown_time: U256,
+    pub mode: BranchMode,
+    pub updated_block: u64,
+}
+
+impl BranchHealth {
+    /// The branch can be shut down by anyone once TCR falls below SCR
+    pub fn below_scr(&self) -> bool {
+        self.tcr < self.scr
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        !self.shutdown_time.is_zero()
+    }
+}
+
+/// Strategy that tracks a branch's total collateral ratio against SCR and switches the branch to
+/// urgent-redemption mode once `TroveManager.shutdownTime` is set
+#[derive(Clone)]
+pub struct BranchMonitor {
+    name: String,
+    trove_manager: Address,
+    oracle: Address,
+    scr: U256,
+    provider: Arc<StrategyProvider>,
+    health: Arc<RwLock<BranchHealth>>,
+}
+
+impl BranchMonitor {
+    pub fn new(
+        trove_manager: Address,
+        oracle: Address,
+        scr: U256,
+        provider: Arc<StrategyProvider>,
+    ) -> Self {
+        Self {
+            name: "BranchMonitor".to_string(),
+            trove_manager,
+            oracle,
+            scr,
+            provider,
+            health: Arc::new(RwLock::new(BranchHealth { scr, ..Default::default() })),
+        }
+    }
+
+    /// Shared handle other strategies read the branch mode from
+    pub fn health(&self) -> Arc<RwLock<BranchHealth>> {
+        self.health.clone()
+    }
+
+    async fn refresh(&self, block_number: u64) -> Result<BranchHealth> {
+        let trove_manager = TroveManager::new(self.trove_manager, &*self.provider);
+        let total_coll = trove_manager.getEntireBranchColl().call().await?;
+        let total_debt = trove_manager.getEntireBranchDebt().call().await?;
+        let shutdown_time = trove_manager.shutdownTime().call().await?;
+
+        let answer = AggregatePriceFeed::new(self.oracle, &*self.provider).latestAnswer().call().await?;
+        let price = U256::try_from(answer).map_err(|_| eyre::eyre!("Negative oracle price"))?
+            * U256::from(10u64.pow(10));
+
+        let tcr = if total_debt.is_zero() { U256::MAX } else { total_coll * price / total_debt };
+        let mode = if shutdown_time.is_zero() {
+            BranchMode::Normal
+        } else {
+            BranchMode::UrgentRedemption
+        };
+
+        Ok(BranchHealth {
+            total_coll,
+            total_debt,
+            price,
+            tcr,
+            scr: self.scr,
+            shutdown_time,
+            mode,
+            updated_block: block_number,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Strategy<u64> for BranchMonitor {
+    async fn execute(&self, block_number: &u64) -> Result<()> {
+        let health = self.refresh(*block_number).await?;
+        let previous_mode = self.health.read().await.mode;
+
+        if health.mode != previous_mode {
+            warn!(
+                "🚨 Branch {:?} switched to {:?} mode (shutdown time: {}, TCR: {})",
+                self.trove_manager, health.mode, health.shutdown_time, health.tcr
+            );
+        } else if health.below_scr() && !health.is_shutdown() {
+            warn!(
+                "🚨 Branch {:?} TCR {} is below SCR {} - branch can be shut down",
+                self.trove_manager, health.tcr, health.scr
+            );
+        } else {
+            info!("🏦 Branch {:?} TCR: {} (SCR: {})", self.trove_manager, health.tcr, health.scr);
+        }
+
+        *self.health.write().await = health;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
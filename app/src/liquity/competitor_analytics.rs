@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use log::{info, warn};
+
+use crate::db::{
+    DatabaseStore,
+    store::{CompetitorStats, LiquidatedTroveInsert, LiquidationInsert},
+};
+
+const GWEI: f64 = 1_000_000_000.0;
+
+/// Attributes every liquidated trove to the transaction that took it and measures how the winner
+/// compared to our own detection and attempts
+#[derive(Clone)]
+pub struct CompetitorAnalytics {
+    store: Arc<dyn DatabaseStore>,
+}
+
+impl CompetitorAnalytics {
+    pub fn new(store: Arc<dyn DatabaseStore>) -> Self {
+        Self { store }
+    }
+
+    /// Attribute the troves liquidated by a recorded `Liquidation` to its sender
+    pub async fn attribute(&self, record: &LiquidationInsert) -> Result<Vec<LiquidatedTroveInsert>> {
+        let trove_ids = self.store.get_troves_liquidated_in_tx(&record.tx_hash).await?;
+        let gas_price_gwei =
+            record.effective_gas_price.parse::<f64>().unwrap_or_default() / GWEI;
+
+        let mut attributed = Vec::with_capacity(trove_ids.len());
+        for trove_id in trove_ids {
+            let candidate = self.store.get_liquidation_candidate(&trove_id).await?;
+            let liquidatable_since_block =
+                candidate.as_ref().map(|candidate| candidate.first_liquidatable_block);
+            let we_attempted = candidate.as_ref().is_some_and(|candidate| candidate.attempted);
+
+            let trove = LiquidatedTroveInsert {
+                trove_id,
+                tx_hash: record.tx_hash.clone(),
+                block_number: record.block_number,
+                sender: record.sender.clone(),
+                is_ours: record.is_ours,
+                effective_gas_price: record.effective_gas_price.clone(),
+                gas_price_gwei,
+                liquidatable_since_block,
+                blocks_after_eligible: liquidatable_since_block
+                    .map(|since| record.block_number - since),
+                we_attempted,
+            };
+            self.store.insert_liquidated_trove(&trove).await?;
+
+            if !trove.is_ours {
+                match trove.blocks_after_eligible {
+                    Some(blocks) => warn!(
+                        "🏁 Lost trove {} to {} - {} blocks after eligible, gas price {:.2} gwei, attempted: {}",
+                        trove.trove_id, trove.sender, blocks, gas_price_gwei, we_attempted
+                    ),
+                    None => warn!(
+                        "🏁 Lost trove {} to {} - never flagged by our model, gas price {:.2} gwei",
+                        trove.trove_id, trove.sender, gas_price_gwei
+                    ),
+                }
+            }
+            attributed.push(trove);
+        }
+
+        Ok(attributed)
+    }
+
+    /// Per-competitor race statistics
+    pub async fn report(&self) -> Result<Vec<CompetitorStats>> {
+        self.store.get_competitor_stats().await
+    }
+
+    /// Log the per-competitor race statistics
+    pub async fn log_report(&self) -> Result<()> {
+        let stats = self.report().await?;
+        if stats.is_empty() {
+            info!("🏁 No competitor liquidations recorded");
+            return Ok(());
+        }
+
+        info!("🏁 Competitor liquidation report:");
+        for row in stats {
+            info!(
+                "🏁   {} - troves: {}, tracked by us: {}, we attempted: {}, blocks after eligible avg/min: {}/{}, gas price avg/max: {:.2}/{:.2} gwei",
+                row.sender,
+                row.troves,
+                row.tracked,
+                row.we_attempted,
+                row.avg_blocks_after_eligible.map_or("-".to_string(), |v| format!("{:.1}", v)),
+                row.min_blocks_after_eligible.map_or("-".to_string(), |v| v.to_string()),
+                row.avg_gas_price_gwei,
+                row.max_gas_price_gwei
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, I256, U256},
+    providers::Provider,
+    rpc::types::Log,
+};
+use eyre::Result;
+use log::info;
+
+use crate::{
+    db::{DatabaseStore, store::LiquidationInsert},
+    liquity::{
+        liquity::TroveManager,
+        liquity_strategy::{AggregatePriceFeed, StrategyProvider, log_block_timestamp},
+    },
+};
+
+const DECIMAL_PRECISION: u128 = 1_000_000_000_000_000_000u128;
+
+/// Persists `Liquidation` events and computes what each liquidation earned its sender
+#[derive(Clone)]
+pub struct LiquidationRecorder {
+    branch: Address,
+    liquidator: Address,
+    native_price_oracle: Option<Address>,
+    store: Arc<dyn DatabaseStore>,
+    provider: Arc<StrategyProvider>,
+}
+
+impl LiquidationRecorder {
+    /// `native_price_oracle` prices the gas token; when unset the gas token is assumed to be the
+    /// branch collateral and the liquidation `_price` is used
+    pub fn new(
+        branch: Address,
+        liquidator: Address,
+        native_price_oracle: Option<Address>,
+        store: Arc<dyn DatabaseStore>,
+        provider: Arc<StrategyProvider>,
+    ) -> Self {
+        Self { branch, liquidator, native_price_oracle, store, provider }
+    }
+
+    /// Record a `Liquidation` event with its gas cost and net profit
+    pub async fn record(
+        &self,
+        event: &TroveManager::Liquidation,
+        log: &Log,
+    ) -> Result<LiquidationInsert> {
+        let tx_hash = log
+            .transaction_hash
+            .ok_or_else(|| eyre::eyre!("Liquidation log is missing its transaction hash"))?;
+        let block_number = log.block_number.unwrap_or_default();
+
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Receipt not found for liquidation tx {:?}", tx_hash))?;
+        let block_timestamp = log_block_timestamp(&self.provider, log).await?;
+
+        let is_ours = receipt.to == Some(self.liquidator);
+        let gas_cost = U256::from(receipt.gas_used) * U256::from(receipt.effective_gas_price);
+
+        let price = event._price;
+        let native_price = self.native_price(block_number, price).await?;
+        let profit = liquidation_profit(
+            event._collGasCompensation,
+            event._boldGasCompensation,
+            gas_cost,
+            price,
+            native_price,
+        );
+
+        let record = LiquidationInsert {
+            branch: format!("{:?}", self.branch),
+            block_number: block_number as i64,
+            block_timestamp: block_timestamp as i64,
+            tx_hash: format!("{:?}", tx_hash),
+            log_index: log.log_index.unwrap_or_default() as i64,
+            sender: format!("{:?}", receipt.from),
+            is_ours,
+            debt_offset_by_sp: event._debtOffsetBySP.to_string(),
+            debt_redistributed: event._debtRedistributed.to_string(),
+            coll_gas_compensation: event._collGasCompensation.to_string(),
+            bold_gas_compensation: event._boldGasCompensation.to_string(),
+            coll_sent_to_sp: event._collSentToSP.to_string(),
+            coll_redistributed: event._collRedistributed.to_string(),
+            coll_surplus: event._collSurplus.to_string(),
+            price: price.to_string(),
+            gas_used: receipt.gas_used as i64,
+            effective_gas_price: receipt.effective_gas_price.to_string(),
+            gas_cost: gas_cost.to_string(),
+            net_profit_coll: profit.net_profit_coll.to_string(),
+            net_profit_usd: profit.net_profit_usd.to_string(),
+            gas_compensation_usd_numeric: units_to_f64(&profit.gas_compensation_usd.to_string()),
+            net_profit_usd_numeric: units_to_f64(&profit.net_profit_usd.to_string()),
+        };
+        self.store.insert_liquidation(&record).await?;
+
+        info!(
+            "💀 Liquidation - Block: {}, tx: {:?}, sender: {:?}, ours: {}, net profit: ${:.2}",
+            block_number, tx_hash, receipt.from, is_ours, record.net_profit_usd_numeric
+        );
+
+        Ok(record)
+    }
+
+    /// Price of the gas token (18 decimals) at the given block
+    async fn native_price(&self, block_number: u64, collateral_price: U256) -> Result<U256> {
+        let Some(oracle) = self.native_price_oracle else {
+            return Ok(collateral_price);
+        };
+
+        let price_feed = AggregatePriceFeed::new(oracle, &*self.provider);
+        let answer =
+            price_feed.latestAnswer().block(BlockId::number(block_number)).call().await?;
+        let answer = U256::try_from(answer)
+            .map_err(|_| eyre::eyre!("Negative native price from oracle {:?}", oracle))?;
+
+        // Convert from 8 decimals to 18 decimals
+        Ok(answer * U256::from(10u64.pow(10)))
+    }
+}
+
+/// What a liquidation earned its sender (18 decimals)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidationProfit {
+    pub gas_compensation_usd: U256,
+    pub net_profit_usd: I256,
+    pub net_profit_coll: I256,
+}
+
+/// Net profit of a liquidation. The collateral gas compensation is valued at the branch `price`;
+/// the WETH gas compensation (`ETH_GAS_COMPENSATION` per trove, reported as
+/// `_boldGasCompensation`) and the gas cost are both valued at the gas token's `native_price`.
+pub fn liquidation_profit(
+    coll_gas_compensation: U256,
+    weth_gas_compensation: U256,
+    gas_cost: U256,
+    price: U256,
+    native_price: U256,
+) -> LiquidationProfit {
+    let precision = U256::from(DECIMAL_PRECISION);
+
+    // Everything the liquidator receives, valued in USD
+    let gas_compensation_usd =
+        (coll_gas_compensation * price + weth_gas_compensation * native_price) / precision;
+    let gas_cost_usd = gas_cost * native_price / precision;
+    let net_profit_usd = signed_diff(gas_compensation_usd, gas_cost_usd);
+
+    // The same outcome expressed in collateral units
+    let net_profit_coll = if price.is_zero() {
+        I256::ZERO
+    } else {
+        let compensation_coll =
+            coll_gas_compensation + weth_gas_compensation * native_price / price;
+        signed_diff(compensation_coll, gas_cost * native_price / price)
+    };
+
+    LiquidationProfit { gas_compensation_usd, net_profit_usd, net_profit_coll }
+}
+
+/// `a - b` as a signed value
+pub fn signed_diff(a: U256, b: U256) -> I256 {
+    if a >= b { I256::from_raw(a - b) } else { -I256::from_raw(b - a) }
+}
+
+/// Convert an 18-decimal amount to a float for aggregation
+pub fn units_to_f64(value: &str) -> f64 {
+    value.parse::<f64>().unwrap_or_default() / DECIMAL_PRECISION as f64
+}
@@ -1,3 +1,6 @@
+// sol!-generated constructors mirror the ABI argument lists
+#![allow(clippy::too_many_arguments)]
+
 use TroveManager::{TroveManagerEvents};
 use alloy::{rpc::types::Log, sol, sol_types::SolEvent};
 use serde::{Deserialize, Serialize};
@@ -10,6 +13,37 @@ sol!(
 );
 
 
+/// Raw values of the `TroveManager.Operation` enum
+pub mod operation {
+    pub const OPEN_TROVE: u8 = 0;
+    pub const CLOSE_TROVE: u8 = 1;
+    pub const ADJUST_TROVE: u8 = 2;
+    pub const ADJUST_TROVE_INTEREST_RATE: u8 = 3;
+    pub const APPLY_PENDING_DEBT: u8 = 4;
+    pub const LIQUIDATE: u8 = 5;
+    pub const REDEEM_COLLATERAL: u8 = 6;
+    pub const OPEN_TROVE_AND_JOIN_BATCH: u8 = 7;
+    pub const SET_INTEREST_BATCH_MANAGER: u8 = 8;
+    pub const REMOVE_FROM_BATCH: u8 = 9;
+
+    /// Solidity name of an operation, as stored in the trove_events table
+    pub fn name(op: u8) -> &'static str {
+        match op {
+            OPEN_TROVE => "openTrove",
+            CLOSE_TROVE => "closeTrove",
+            ADJUST_TROVE => "adjustTrove",
+            ADJUST_TROVE_INTEREST_RATE => "adjustTroveInterestRate",
+            APPLY_PENDING_DEBT => "applyPendingDebt",
+            LIQUIDATE => "liquidate",
+            REDEEM_COLLATERAL => "redeemCollateral",
+            OPEN_TROVE_AND_JOIN_BATCH => "openTroveAndJoinBatch",
+            SET_INTEREST_BATCH_MANAGER => "setInterestBatchManager",
+            REMOVE_FROM_BATCH => "removeFromBatch",
+            _ => "unknown",
+        }
+    }
+}
+
 pub fn decode_event_log(log: &Log) -> Option<TroveManagerEvents> {
     if log.topics().is_empty() {
         return None;
@@ -19,7 +53,17 @@ pub fn decode_event_log(log: &Log) -> Option<TroveManagerEvents> {
         x if x == TroveManager::TroveUpdated::SIGNATURE_HASH => {
             let event = log.log_decode::<TroveManager::TroveUpdated>().unwrap();
             let event = event.data().to_owned();
-            return Some(TroveManagerEvents::TroveUpdated(event));
+            Some(TroveManagerEvents::TroveUpdated(event))
+        }
+        x if x == TroveManager::TroveOperation::SIGNATURE_HASH => {
+            let event = log.log_decode::<TroveManager::TroveOperation>().ok()?;
+            let event = event.data().to_owned();
+            Some(TroveManagerEvents::TroveOperation(event))
+        }
+        x if x == TroveManager::Liquidation::SIGNATURE_HASH => {
+            let event = log.log_decode::<TroveManager::Liquidation>().ok()?;
+            let event = event.data().to_owned();
+            Some(TroveManagerEvents::Liquidation(event))
         }
         _ => None,
     }
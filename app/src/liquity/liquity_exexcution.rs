@@ -1,17 +1,21 @@
+// sol!-generated constructors mirror the ABI argument lists
+#![allow(clippy::too_many_arguments)]
+
 use std::sync::Arc;
 
 use alloy::{
-    primitives::{Address, Bytes, Uint, U256}, 
+    primitives::{Address, Bytes, TxHash, Uint, U256}, 
     providers::{
-         ext::TraceApi, Provider
+         ext::TraceApi, Provider, WalletProvider
     },
      rpc::types::TransactionRequest, sol, sol_types::SolCall
     
 };
 use eyre::Result;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
-use crate::{liquity::{liquity_exexcution::LiquityLiquidator::LiquityLiquidatorInstance, liquity_strategy::StrategyProvider}, DefaultProvider};
+use crate::{db::{DatabaseStore, store::ShadowLiquidationInsert}, metrics, notifier::{Alert, AlertKind, Notifier}, liquity::{liquity_exexcution::LiquityLiquidator::LiquityLiquidatorInstance, liquity_strategy::StrategyProvider}, DefaultProvider};
 
 sol!(
     #[derive(Debug, Default, Serialize, Deserialize)]
@@ -27,6 +31,20 @@ sol!(
     "../artifacts/LiquityLiquidator.sol/LiquidationExecutor.json"
 );
 
+pub const LIQUIDATION_GAS_LIMIT: u64 = 1_500_000;
+pub const LIQUIDATION_GAS_PRICE: u128 = 1_000_000_000;
+/// Liquity v2 pays the liquidator 0.5% of each trove's collateral, capped at 2 units...
+const COLL_GAS_COMPENSATION_DIVISOR: u64 = 200;
+const COLL_GAS_COMPENSATION_CAP: u128 = 2_000_000_000_000_000_000;
+/// ...plus a fixed 0.0375 WETH per trove
+pub const ETH_GAS_COMPENSATION: u128 = 37_500_000_000_000_000;
+
+/// Collateral the liquidator receives for a trove holding `entire_coll`
+pub fn coll_gas_compensation(entire_coll: U256) -> U256 {
+    (entire_coll / U256::from(COLL_GAS_COMPENSATION_DIVISOR))
+        .min(U256::from(COLL_GAS_COMPENSATION_CAP))
+}
+
 
 #[derive(Clone)]
 pub struct LiquityExecutor{
@@ -34,6 +52,9 @@ pub struct LiquityExecutor{
     liquidator_instance:LiquityLiquidatorInstance<Arc<DefaultProvider>>,
     http_provider: Arc<DefaultProvider>,
     provider: Arc<StrategyProvider>,
+    notifier: Notifier,
+    /// Set in shadow mode: nothing is broadcast, liquidations are recorded here instead
+    shadow_store: Option<Arc<dyn DatabaseStore>>,
 }
 
 impl LiquityExecutor{
@@ -43,10 +64,26 @@ impl LiquityExecutor{
             trove_manager,
             liquidator_instance,
             provider,
-            http_provider
+            http_provider,
+            notifier: Notifier::disabled(),
+            shadow_store: None,
         }
     }
 
+    pub fn set_notifier(&mut self, notifier: Notifier) {
+        self.notifier = notifier;
+    }
+
+    /// Never broadcast: build and simulate every transaction, and record liquidations in the
+    /// `shadow_liquidations` table
+    pub fn set_shadow_mode(&mut self, store: Arc<dyn DatabaseStore>) {
+        self.shadow_store = Some(store);
+    }
+
+    pub fn is_shadow(&self) -> bool {
+        self.shadow_store.is_some()
+    }
+
       fn encode_call(&self , trove_ids: Vec<Uint<256,4>>)->Result<Vec<u8>>{
        Ok(TroveManager::batchLiquidateTrovesCall{ _troveArray: trove_ids}.abi_encode())
       
@@ -57,13 +94,24 @@ impl LiquityExecutor{
     async fn submit_liquidate_txn(
         &self,
         liquidate_txn: TransactionRequest,
-    ) -> Result<()> {
+    ) -> Result<TxHash> {
         let sendable_tx = self.liquidator_instance.provider().fill(liquidate_txn).await?;
         let send_result =
             self.http_provider.send_tx_envelope(sendable_tx.as_envelope().unwrap().clone()).await;
         let sent_tx = match send_result {
             Ok(tx) => {
                 println!("✅ txn sent: {:?}", tx.tx_hash());
+                metrics::LIQUIDATION_TXS_SENT.inc();
+                self.notifier
+                    .notify(
+                        Alert::new(
+                            AlertKind::LiquidationSent,
+                            format!("sent:{:?}", tx.tx_hash()),
+                            "Liquidation transaction sent",
+                        )
+                        .field("tx", format!("{:?}", tx.tx_hash())),
+                    )
+                    .await;
                
                 tx
             }
@@ -83,6 +131,25 @@ impl LiquityExecutor{
         match receipt_result {
             Ok(receipt_result) => {
                 println!("📑 receipt: {:?}", receipt_result);
+                let (kind, title) = if receipt_result.status() {
+                    metrics::LIQUIDATION_TXS_MINED.inc();
+                    (AlertKind::LiquidationMined, "Liquidation transaction mined")
+                } else {
+                    metrics::LIQUIDATION_TXS_REVERTED.inc();
+                    (AlertKind::LiquidationReverted, "Liquidation transaction reverted")
+                };
+                let gas_cost =
+                    receipt_result.gas_used as f64 * receipt_result.effective_gas_price as f64;
+                metrics::GAS_SPENT.inc_by(gas_cost);
+                self.notifier
+                    .notify(
+                        Alert::new(kind, format!("{}:{:?}", kind.as_str(), tx_hash), title)
+                            .field("tx", format!("{:?}", tx_hash))
+                            .field("block", receipt_result.block_number.unwrap_or_default())
+                            .field("gas_used", receipt_result.gas_used)
+                            .field("gas_cost_wei", gas_cost),
+                    )
+                    .await;
                 receipt_result
             }
             Err(e) => {
@@ -91,19 +158,128 @@ impl LiquityExecutor{
             }
         };
 
-        Ok(())
+        Ok(tx_hash)
     }
 
 
-    pub async fn execute(&self , trove_ids: Vec<Uint<256,4>>)->Result<()>{
+    /// Estimate gas for liquidating `trove_ids` from the signer without sending anything. Fails
+    /// when the call would revert.
+    pub async fn simulate(&self, trove_ids: Vec<Uint<256, 4>>) -> Result<u64> {
+        let encoded_data = self.encode_call(trove_ids)?;
+        let gas = self
+            .liquidator_instance
+            .execute(self.trove_manager, U256::ZERO, encoded_data.into())
+            .from(self.http_provider.default_signer_address())
+            .estimate_gas()
+            .await?;
+        Ok(gas)
+    }
+
+    /// Liquidate `trove_ids`, found liquidatable at `block_number`; in shadow mode they are
+    /// simulated and recorded and `None` is returned
+    pub async fn execute(
+        &self,
+        trove_ids: Vec<Uint<256, 4>>,
+        block_number: u64,
+    ) -> Result<Option<TxHash>> {
+        if let Some(store) = &self.shadow_store {
+            self.shadow_liquidate(store.as_ref(), trove_ids, block_number).await?;
+            return Ok(None);
+        }
         let encoded_data = self.encode_call(trove_ids)?;
-        let encoded_bytes: Bytes = encoded_data.into();
+        self.execute_call(self.trove_manager, encoded_data.into()).await
+    }
 
-        let liquidate_txn = self.liquidator_instance.execute(self.trove_manager, U256::ZERO, encoded_bytes).gas(1_500_000)
-        .gas_price(1_000_000_000)
-        .into_transaction_request();
+    /// Send an arbitrary call through `LiquidationExecutor.execute`; in shadow mode it is only
+    /// simulated and `None` is returned
+    pub async fn execute_call(&self, target: Address, data: Bytes) -> Result<Option<TxHash>> {
+        let txn = self.build_txn(target, data);
+        if self.is_shadow() {
+            match self.http_provider.estimate_gas(txn).await {
+                Ok(gas) => {
+                    info!("🕶️ Shadow mode - call to {:?} simulates fine ({} gas)", target, gas)
+                }
+                Err(e) => warn!("🕶️ Shadow mode - call to {:?} would revert: {}", target, e),
+            }
+            return Ok(None);
+        }
 
-        self.submit_liquidate_txn(liquidate_txn).await
-         
+        self.submit_liquidate_txn(txn).await.map(Some)
     }
-}
\ No newline at end of file
+
+    fn build_txn(&self, target: Address, data: Bytes) -> TransactionRequest {
+        self.liquidator_instance
+            .execute(target, U256::ZERO, data)
+            .from(self.http_provider.default_signer_address())
+            .gas(LIQUIDATION_GAS_LIMIT)
+            .gas_price(LIQUIDATION_GAS_PRICE)
+            .into_transaction_request()
+    }
+
+    /// Simulate a liquidation and record what it would have cost and earned, without sending it.
+    /// Troves already recorded keep the block shadow mode first decided to liquidate them at.
+    async fn shadow_liquidate(
+        &self,
+        store: &dyn DatabaseStore,
+        trove_ids: Vec<Uint<256, 4>>,
+        block_number: u64,
+    ) -> Result<()> {
+        let mut new_trove_ids = Vec::with_capacity(trove_ids.len());
+        for trove_id in trove_ids {
+            if !store.has_shadow_liquidation(&trove_id.to_string()).await? {
+                new_trove_ids.push(trove_id);
+            }
+        }
+        if new_trove_ids.is_empty() {
+            return Ok(());
+        }
+        let trove_ids = new_trove_ids;
+
+        let data = self.encode_call(trove_ids.clone())?;
+        let txn = self.build_txn(self.trove_manager, data.into());
+        let simulation = self.http_provider.estimate_gas(txn).await;
+
+        let trove_manager = TroveManager::new(self.trove_manager, &*self.provider);
+        let mut compensations = Vec::with_capacity(trove_ids.len());
+        for trove_id in &trove_ids {
+            let trove = trove_manager.getLatestTroveData(*trove_id).call().await?;
+            compensations.push(coll_gas_compensation(trove.entireColl));
+        }
+        let total_coll: U256 = compensations.iter().copied().sum();
+        let total_eth = U256::from(ETH_GAS_COMPENSATION) * U256::from(trove_ids.len());
+
+        let (estimated_gas, error) = match &simulation {
+            Ok(gas) => {
+                info!(
+                    "🕶️ Shadow liquidation at block {} - troves: {:?}, estimated gas: {}, expected compensation: {} coll + {} WETH",
+                    block_number, trove_ids, gas, total_coll, total_eth
+                );
+                (Some(*gas as i64), None)
+            }
+            Err(e) => {
+                warn!(
+                    "🕶️ Shadow liquidation at block {} would revert - troves: {:?}: {}",
+                    block_number, trove_ids, e
+                );
+                (None, Some(e.to_string()))
+            }
+        };
+
+        for (trove_id, compensation) in trove_ids.iter().zip(compensations) {
+            store
+                .record_shadow_liquidation(&ShadowLiquidationInsert {
+                    trove_id: trove_id.to_string(),
+                    block_number: block_number as i64,
+                    batch_size: trove_ids.len() as i64,
+                    estimated_gas,
+                    gas_price: LIQUIDATION_GAS_PRICE.to_string(),
+                    expected_coll_compensation: compensation.to_string(),
+                    expected_eth_compensation: ETH_GAS_COMPENSATION.to_string(),
+                    simulated_ok: simulation.is_ok(),
+                    error: error.clone(),
+                })
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,29 @@
-use std::sync::Arc;
+// sol!-generated constructors mirror the ABI argument lists
+#![allow(clippy::too_many_arguments)]
+
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    db::{DatabaseStore, store::Trove},
+    config::CandidateSource,
+    db::{
+        DatabaseStore,
+        store::{Trove, TroveEventInsert, TroveStatus},
+    },
+    metrics,
     liquity::{
-        liquity::{TroveManager::TroveManagerEvents, decode_event_log},
+        liquity::{TroveManager::TroveManagerEvents, decode_event_log, operation},
+        competitor_analytics::CompetitorAnalytics,
+        liquidation_recorder::{LiquidationRecorder, units_to_f64},
         liquity_exexcution::LiquityExecutor,
+        recheck_scheduler::{RecheckScheduler, project_liquidation_time},
+        sorted_troves::SortedTrovesMirror,
         trove_memory_cache::TroveMemoryCache,
     },
     strategy::Strategy,
 };
 use alloy::{
     eips::BlockNumberOrTag,
-    primitives::{Address, U256, Uint},
+    primitives::{Address, U256, U512, Uint},
     providers::{
         Identity, Provider, RootProvider,
         fillers::{BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller},
@@ -20,9 +32,10 @@ use alloy::{
     sol,
 };
 use eyre::Result;
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use tokio::sync::RwLock;
 
 sol!(
     #[derive(Debug, Default, Serialize, Deserialize)]
@@ -59,38 +72,126 @@ pub type StrategyProvider = FillProvider<
     RootProvider,
 >;
 
-static DECIMAL_PRECISION: u128 = 1_000_000_000_000_000_000u128;
-const ONE_YEAR: u64 = 31_536_000;
+/// Timestamp of the given block
+pub async fn block_timestamp_of(provider: &StrategyProvider, block_number: u64) -> Result<u64> {
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Number(block_number))
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block {} not found", block_number))?;
+    Ok(block.header.timestamp)
+}
+
+/// Timestamp of the block a log was emitted in, fetching the block when the node omits it
+pub async fn log_block_timestamp(provider: &StrategyProvider, log: &Log) -> Result<u64> {
+    match log.block_timestamp {
+        Some(timestamp) => Ok(timestamp),
+        None => block_timestamp_of(provider, log.block_number.unwrap_or_default()).await,
+    }
+}
+
+pub(crate) const DECIMAL_PRECISION: u128 = 1_000_000_000_000_000_000u128;
+pub(crate) const ONE_YEAR: u64 = 31_536_000;
+/// `MIN_DEBT`: a redemption leaving less debt than this turns the trove into a zombie
+const MIN_DEBT: u128 = 2_000_000_000_000_000_000_000;
+/// How often trove book statistics are logged
+const CACHE_REPORT_INTERVAL_BLOCKS: u64 = 100;
+/// How often a seeded SortedTroves mirror is cross-checked against the DB
+const CROSS_CHECK_INTERVAL_BLOCKS: u64 = 100;
+/// Trove ids logged per side of a failed cross-check
+const CROSS_CHECK_LOGGED_IDS: usize = 5;
+/// Default for how far ahead interest is accrued into a trove's liquidation price
+const DEFAULT_LIQUIDATION_PRICE_HORIZON_SECS: u64 = 3600;
+/// Troves walked in ICR order when candidates come from the stored ICR
+pub const MAX_ICR_CANDIDATES: usize = 50;
+/// Re-project every active trove's interest-driven liquidation time at least this often
+const PROJECTION_INTERVAL_BLOCKS: u64 = 300;
+/// ...or as soon as the oracle price has fallen this much (basis points) from the highest price
+/// a pending re-check was projected at. A rise only delays crossings, and re-checks that fire
+/// early are re-projected individually.
+const PROJECTION_PRICE_DROP_BPS: u64 = 100;
+
+/// Outcome of checking a list of troves against MCR
+#[derive(Debug, Clone, Default)]
+pub struct TroveScan {
+    pub liquidatable: Vec<Uint<256, 4>>,
+    pub lowest_icr: Option<Uint<256, 4>>,
+}
+
+/// Check `troves` in order at `block_timestamp` and `price`. With `sorted_by_icr` the walk stops
+/// at the first healthy trove. Pure, so the backtester runs exactly what the strategy runs.
+pub fn scan_troves(
+    troves: &[Trove],
+    block_timestamp: u64,
+    price: Uint<256, 4>,
+    mcr: Uint<256, 4>,
+    sorted_by_icr: bool,
+) -> TroveScan {
+    let mut scan = TroveScan { liquidatable: Vec::with_capacity(32), lowest_icr: None };
+
+    // Process troves with minimal allocations
+    for trove in troves {
+        // Early validation - skip invalid troves immediately
+        let Some(full_icr) = LiquityStrategy::trove_icr(trove, block_timestamp, price) else {
+            continue;
+        };
+        scan.lowest_icr = Some(scan.lowest_icr.map_or(full_icr, |lowest| lowest.min(full_icr)));
+
+        if full_icr < mcr {
+            // Parse trove_id only when needed
+            if let Ok(trove_id) = Uint::<256, 4>::from_str(&trove.trove_id) {
+                scan.liquidatable.push(trove_id);
+                info!("🔍 Trove {} - ICR: {} - LIQUIDATABLE", &trove.trove_id, full_icr);
+                info!("🔍 Collateral {} - Debt {}", trove.collateral, trove.debt);
+            }
+        } else if sorted_by_icr {
+            // Since troves are sorted by risk, we can break early
+            info!(
+                "🔍 Trove {} - ICR: {} - NOT LIQUIDATABLE (stopping)",
+                &trove.trove_id, full_icr
+            );
+            info!("🔍 Collateral {} - Debt {}", trove.collateral, trove.debt);
+            break;
+        }
+    }
+    scan
+}
 
-/// Liquity Strategy that monitors and processes TroveUpdated events
+/// Liquity Strategy that monitors and processes TroveUpdated, TroveOperation and Liquidation events
 #[derive(Clone)]
 
 pub struct LiquityStrategy {
     name: String,
     trove_manager: Address,
-    store: Arc<DatabaseStore>,
+    store: Arc<dyn DatabaseStore>,
     provider: Arc<StrategyProvider>,
     oracle: Address,
     mcr: Uint<256, 4>,         // Chainlink ETH/USD
     executor: LiquityExecutor, // Your adapted executor
     memory_cache: TroveMemoryCache,
-   
+    liquidation_recorder: LiquidationRecorder,
+    competitor_analytics: CompetitorAnalytics,
+    candidate_source: CandidateSource,
+    sorted_troves: Option<SortedTrovesMirror>,
+    liquidation_price_horizon_secs: u64,
+    rechecks: RecheckScheduler,
+    /// Block of the last full projection pass and the highest price projected at since
+    last_projection: Arc<RwLock<Option<(u64, Uint<256, 4>)>>>,
 }
 
 impl LiquityStrategy {
     /// Create a new Liquity strategy
     pub async fn new(
         trove_manager: Address,
-        store: Arc<DatabaseStore>,
+        store: Arc<dyn DatabaseStore>,
         provider: Arc<StrategyProvider>,
         oracle_address: Address,
         mcr: Uint<256, 4>,
         executor: LiquityExecutor,
-        
-       
+        liquidation_recorder: LiquidationRecorder,
     ) -> Self {
 
-        let memory_cache = TroveMemoryCache::new(2000000);
+        let memory_cache = TroveMemoryCache::new();
+        let competitor_analytics = CompetitorAnalytics::new(store.clone());
 
 
         Self {
@@ -102,16 +203,94 @@ impl LiquityStrategy {
             mcr,
             executor, // executor,
             memory_cache,
-           
+            liquidation_recorder,
+            competitor_analytics,
+            candidate_source: CandidateSource::Database,
+            sorted_troves: None,
+            liquidation_price_horizon_secs: DEFAULT_LIQUIDATION_PRICE_HORIZON_SECS,
+            rechecks: RecheckScheduler::new(),
+            last_projection: Arc::new(RwLock::new(None)),
         }
     }
 
-    /// Process a TroveUpdated event
-    async fn process_trove_event(
-        &self,
-        events: &TroveManagerEvents,
-        block_number: u64,
-    ) -> Result<()> {
+    /// How far ahead interest is accrued into the precomputed liquidation prices
+    pub fn set_liquidation_price_horizon(&mut self, horizon_secs: u64) {
+        self.liquidation_price_horizon_secs = horizon_secs;
+    }
+
+    /// Shared handle to the in-memory trove book
+    pub fn memory_cache(&self) -> TroveMemoryCache {
+        self.memory_cache.clone()
+    }
+
+    /// Take candidates from a SortedTroves mirror, kept in sync from trove events
+    pub fn set_sorted_troves(&mut self, mirror: SortedTrovesMirror) {
+        self.sorted_troves = Some(mirror);
+        self.candidate_source = CandidateSource::SortedTroves;
+    }
+
+    /// Take candidates from `source`; `SortedTroves` needs `set_sorted_troves` instead
+    pub fn set_candidate_source(&mut self, source: CandidateSource) {
+        self.candidate_source = source;
+    }
+
+    /// Every listed trove, walked from the last node. SortedTroves orders by interest rate, not
+    /// ICR, so any prefix of the list could miss an undercollateralized trove.
+    async fn sorted_troves_candidates(&self, mirror: &SortedTrovesMirror) -> Result<Vec<Trove>> {
+        let ids: Vec<String> = mirror
+            .candidates(mirror.size().await)
+            .await
+            .into_iter()
+            .map(|id| id.to_string())
+            .collect();
+        self.memory_cache.ensure_loaded(&self.store).await?;
+        Ok(self.memory_cache.get_many(&ids))
+    }
+
+    /// Log and export how the SortedTroves mirror disagrees with the DB, if one is seeded
+    async fn cross_check_sorted_troves(&self, block_number: u64) -> Result<()> {
+        let Some(mirror) = &self.sorted_troves else {
+            return Ok(());
+        };
+        self.memory_cache.ensure_loaded(&self.store).await?;
+        let check = mirror.cross_check(&self.memory_cache.all()).await;
+        metrics::record_sorted_troves_cross_check(&check);
+
+        let summary = format!(
+            "📜 Block {} - SortedTroves cross-check: {} listed vs {} active in DB, {} missing from list, {} listed but not open in DB, {} rate inversions",
+            block_number,
+            check.listed,
+            check.active,
+            check.missing_from_list.len(),
+            check.missing_from_db.len(),
+            check.rate_inversions
+        );
+        if check.missing_from_list.is_empty() && check.missing_from_db.is_empty() {
+            info!("{}", summary);
+        } else {
+            let sample =
+                |ids: &[String]| ids.iter().take(CROSS_CHECK_LOGGED_IDS).cloned().collect::<Vec<_>>();
+            warn!(
+                "{} - e.g. unlisted: {:?}, not in DB: {:?}",
+                summary,
+                sample(&check.missing_from_list),
+                sample(&check.missing_from_db)
+            );
+        }
+        Ok(())
+    }
+
+    /// Process a TroveUpdated, TroveOperation or Liquidation event
+    async fn process_trove_event(&self, events: &TroveManagerEvents, log: &Log) -> Result<()> {
+        let block_number = log.block_number.unwrap_or_default();
+        let event_name = match events {
+            TroveManagerEvents::TroveUpdated(_) => "TroveUpdated",
+            TroveManagerEvents::TroveOperation(_) => "TroveOperation",
+            TroveManagerEvents::Liquidation(_) => "Liquidation",
+            _ => "Other",
+        };
+        metrics::LOGS_PROCESSED.with_label_values(&[event_name]).inc();
+
         match events {
             TroveManagerEvents::TroveUpdated(event) => {
                 info!(
@@ -127,29 +306,106 @@ impl LiquityStrategy {
                 let coll = event._coll;
                 let debt = event._debt;
 
-                // Handle zero debt/coll as closed/liquidated
-                let status = if debt == Uint::ZERO && coll == Uint::ZERO {
-                    "closed".to_string()
-                } else {
-                    "active".to_string()
-                };
-
-                // For DB insert, compute full ICR with current price (but we'll re-compute on checks)
-                let icr = if debt != Uint::ZERO { (coll) / debt } else { Uint::ZERO };
-                let icr_numeric: f64 = icr.try_into().unwrap(); // For sorting
+                // Zero debt/coll means the trove was closed; the following TroveOperation tells us
+                // whether by its owner or by liquidation, so keep any closed status already set
+                let existing = self.store.get_trove_by_id(&trove_id).await?;
+                let existing_status =
+                    existing.as_ref().and_then(|trove| TroveStatus::parse(&trove.status));
+                let status = Self::updated_status(coll, debt, existing_status);
+
+                let updated_at = log_block_timestamp(&self.provider, log).await?;
+                let (liquidation_price, liquidation_price_until) = self.liquidation_price_entry(
+                    debt,
+                    coll,
+                    event._annualInterestRate,
+                    updated_at,
+                    updated_at,
+                );
 
-                self.store
-                    .upsert_trove(&Trove {
+                self.memory_cache
+                    .upsert(&self.store, &Trove {
                         trove_id,
-                        collateral: coll.to_string(),
-                        debt: debt.to_string(),
-                        icr: icr.to_string(),
-                        icr_numeric,
-                        status,
-                        interest_rate: event._annualInterestRate.to_string(),
+                        collateral: coll.into(),
+                        debt: debt.into(),
+                        icr_key: Trove::icr_key(coll, debt),
+                        status: status.as_str().to_string(),
+                        interest_rate: event._annualInterestRate.into(),
                         last_updated: block_number as i64,
+                        last_updated_at: updated_at as i64,
+                        liquidation_price: liquidation_price.into(),
+                        liquidation_price_until,
+                    })
+                    .await?;
+
+                if let Some(mirror) = &self.sorted_troves {
+                    mirror.sync_trove(event._troveId).await?;
+                }
+
+                let projection_price = self.last_projection.read().await.map(|(_, price)| price);
+                match projection_price {
+                    Some(price) if status.is_open() => {
+                        self.schedule_recheck(
+                            event._troveId,
+                            debt,
+                            coll,
+                            event._annualInterestRate,
+                            updated_at,
+                            price,
+                        )
+                        .await
+                    }
+                    _ => self.rechecks.cancel(event._troveId).await,
+                }
+            }
+            TroveManagerEvents::TroveOperation(event) => {
+                let op = event._operation;
+                info!(
+                    "🛡️ TroveOperation - Block: {}, trove_id: {}, Operation: {}",
+                    block_number, event._troveId, op
+                );
+
+                // TroveManager emits TroveUpdated first, so the stored debt is already the
+                // post-operation debt
+                let existing = self.store.get_trove_by_id(&event._troveId.to_string()).await?;
+                let status = Self::operation_status(
+                    op,
+                    existing.as_ref().map(|trove| trove.debt()),
+                    existing.as_ref().and_then(|trove| TroveStatus::parse(&trove.status)),
+                );
+
+                self.store
+                    .insert_trove_event(&TroveEventInsert {
+                        trove_id: event._troveId.to_string(),
+                        block_number: block_number as i64,
+                        tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+                        log_index: log.log_index.unwrap_or_default() as i64,
+                        operation: operation::name(op).to_string(),
+                        annual_interest_rate: event._annualInterestRate.to_string(),
+                        debt_increase_from_redist: event._debtIncreaseFromRedist.to_string(),
+                        debt_increase_from_upfront_fee: event._debtIncreaseFromUpfrontFee.to_string(),
+                        debt_change_from_operation: event._debtChangeFromOperation.to_string(),
+                        coll_increase_from_redist: event._collIncreaseFromRedist.to_string(),
+                        coll_change_from_operation: event._collChangeFromOperation.to_string(),
                     })
                     .await?;
+
+                if let Some(status) = status {
+                    self.memory_cache
+                        .set_status(
+                            &self.store,
+                            &event._troveId.to_string(),
+                            status,
+                            block_number as i64,
+                        )
+                        .await?;
+                }
+            }
+            TroveManagerEvents::Liquidation(event) => {
+                let record = self.liquidation_recorder.record(event, log).await?;
+                let attributed = self.competitor_analytics.attribute(&record).await?;
+                if !record.is_ours && !attributed.is_empty() {
+                    self.competitor_analytics.log_report().await?;
+                }
             }
             _ => {
                 info!("📋 Other Aave Event - Block: {}", block_number);
@@ -158,92 +414,157 @@ impl LiquityStrategy {
         Ok(())
     }
 
+    /// Status of a trove after a `TroveUpdated` event, given the status stored before it
+    pub fn updated_status(
+        coll: Uint<256, 4>,
+        debt: Uint<256, 4>,
+        existing_status: Option<TroveStatus>,
+    ) -> TroveStatus {
+        if debt == Uint::ZERO && coll == Uint::ZERO {
+            match existing_status {
+                Some(status) if status.is_closed() => status,
+                _ => TroveStatus::ClosedByOwner,
+            }
+        } else if existing_status == Some(TroveStatus::Zombie) {
+            TroveStatus::Zombie
+        } else {
+            TroveStatus::Active
+        }
+    }
+
+    /// Status of a trove after a `TroveOperation`, given its debt once the operation applied and
+    /// the status stored before it; `None` leaves the stored status alone. Redemptions leave a
+    /// trove below `MIN_DEBT` as a zombie, and adjustments that bring a zombie back to `MIN_DEBT`
+    /// revive it, mirroring `TroveManager`.
+    pub fn operation_status(
+        op: u8,
+        debt: Option<Uint<256, 4>>,
+        existing_status: Option<TroveStatus>,
+    ) -> Option<TroveStatus> {
+        let min_debt = Uint::from(MIN_DEBT);
+        match op {
+            operation::OPEN_TROVE | operation::OPEN_TROVE_AND_JOIN_BATCH => {
+                Some(TroveStatus::Active)
+            }
+            operation::CLOSE_TROVE => Some(TroveStatus::ClosedByOwner),
+            operation::LIQUIDATE => Some(TroveStatus::ClosedByLiquidation),
+            operation::REDEEM_COLLATERAL => match debt {
+                Some(debt) if debt < min_debt => Some(TroveStatus::Zombie),
+                _ => None,
+            },
+            operation::ADJUST_TROVE | operation::APPLY_PENDING_DEBT => {
+                match (debt, existing_status) {
+                    (Some(debt), Some(TroveStatus::Zombie)) if debt >= min_debt => {
+                        Some(TroveStatus::Active)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     /// Check for liquidation opportunities
     pub async fn check_for_liquidation_opportunities(
         &self,
-        timestamp: u64,
+        block_number: u64,
+        block_timestamp: u64,
     ) -> Result<Vec<Uint<256, 4>>> {
         let start_time = std::time::Instant::now();
-        let mut liquidatable: Vec<Uint<256, 4>> = Vec::with_capacity(32);
 
-        // Get troves - from memory if available, otherwise from DB
-        let sorted_troves = self.memory_cache.get_sorted_troves(&self.store).await?;
+        // Fetch oracle price once
+        let price = self.get_oracle_price().await?;
+        self.refresh_liquidation_prices(block_timestamp).await?;
+
+        self.update_projections(block_number, price).await?;
+
+        // Get troves - from the price index, the SortedTroves mirror or the ICR-sorted memory/DB
+        let (mut sorted_troves, mut sorted_by_icr) = match (&self.candidate_source, &self.sorted_troves) {
+            (CandidateSource::PriceIndex, _) => (
+                self.memory_cache
+                    .above_liquidation_price(&self.store, price)
+                    .await?,
+                false,
+            ),
+            (CandidateSource::SortedTroves, Some(mirror)) => {
+                (self.sorted_troves_candidates(mirror).await?, false)
+            }
+            _ => (self.memory_cache.get_sorted_troves(&self.store, MAX_ICR_CANDIDATES).await?, true),
+        };
+
+        // Troves whose projected interest-driven crossing is due are checked first
+        let due_ids: Vec<String> = self
+            .rechecks
+            .take_due(block_timestamp)
+            .await
+            .into_iter()
+            .map(|id| id.to_string())
+            .collect();
+        let due_troves = self.memory_cache.get_many(&due_ids);
+        if !due_troves.is_empty() {
+            info!("⏰ {} troves due for an interest re-check", due_troves.len());
+            sorted_troves.retain(|trove| !due_ids.contains(&trove.trove_id));
+            sorted_troves.splice(0..0, due_troves.iter().cloned());
+            sorted_by_icr = false;
+        }
 
+        metrics::ACTIVE_TROVES.set(self.memory_cache.trove_count() as i64);
         if sorted_troves.is_empty() {
-            return Ok(liquidatable);
+            return Ok(Vec::new());
         }
 
         info!("Checking {} troves for liquidation", sorted_troves.len());
+        let TroveScan { liquidatable, lowest_icr } =
+            scan_troves(&sorted_troves, block_timestamp, price, self.mcr, sorted_by_icr);
 
-        // Fetch oracle price once
-        let price = self.get_oracle_price().await?;
-        let mcr = self.mcr;
-        let zero = Uint::<256, 4>::ZERO;
-        let timestamp_u64 = timestamp;
-
-        // Process troves with minimal allocations
-        for trove in sorted_troves {
-            // Early validation - skip invalid troves immediately
-            let coll = match Uint::<256, 4>::from_str(&trove.collateral) {
-                Ok(val) if val != zero => val,
-                _ => continue,
-            };
-
-            let debt = match Uint::<256, 4>::from_str(&trove.debt) {
-                Ok(val) if val != zero => val,
-                _ => continue,
+        // Due troves that are still healthy (the price moved up) get a fresh projection
+        for trove in due_troves {
+            let Ok(trove_id) = Uint::<256, 4>::from_str(&trove.trove_id) else {
+                continue;
             };
+            if liquidatable.contains(&trove_id) {
+                continue;
+            }
+            // Rounding can put the projection at or before now; never re-check before the next block
+            if let Some(at) = self.project_trove(&trove, price) {
+                self.rechecks.schedule(trove_id, at.max(block_timestamp + 1)).await;
+            }
+        }
 
-            let interest_rate = match Uint::<256, 4>::from_str(&trove.interest_rate) {
-                Ok(val) => val,
-                Err(_) => continue,
-            };
+        if let Some(lowest_icr) = lowest_icr {
+            metrics::LOWEST_ICR.set(units_to_f64(&lowest_icr.to_string()));
+        }
 
-            // Calculate ICR
-            let full_icr = self.calculate_full_icr(
-                debt,
-                coll,
-                interest_rate,
-                timestamp_u64,
-                trove.last_updated as u64,
-                price,
-            );
+        if !liquidatable.is_empty() {
+            info!("Found {} liquidatable troves", liquidatable.len());
+            metrics::LIQUIDATION_CANDIDATES.inc_by(liquidatable.len() as u64);
 
-            if full_icr < mcr {
-                // Parse trove_id only when needed
-                if let Ok(trove_id) = Uint::<256, 4>::from_str(&trove.trove_id) {
-                    liquidatable.push(trove_id);
-                    {
-                        info!("🔍 Trove {} - ICR: {} - LIQUIDATABLE", &trove.trove_id, full_icr);
-                        info!("🔍 Collateral {} - Debt {}", coll, debt);
-                    }
-                }
+            self.store.record_liquidation_candidates(&liquidatable, block_number as i64).await?;
+            // Shadow mode attempts nothing; the troves stay open until liquidated on-chain
+            if self.executor.is_shadow() {
+                self.executor.execute(liquidatable.clone(), block_number).await?;
             } else {
-                // Since troves are sorted by risk, we can break early
-
+                self.store
+                    .mark_candidates_attempted(&liquidatable, block_number as i64, None)
+                    .await?;
+                let _ = self
+                    .memory_cache
+                    .close(&self.store, &liquidatable, block_number as i64)
+                    .await;
+                if let Some(tx_hash) =
+                    self.executor.execute(liquidatable.clone(), block_number).await?
                 {
-                    info!(
-                        "🔍 Trove {} - ICR: {} - NOT LIQUIDATABLE (stopping)",
-                        &trove.trove_id, full_icr
-                    );
-                    info!("🔍 Collateral {} - Debt {}", coll, debt);
+                    self.store
+                        .mark_candidates_attempted(
+                            &liquidatable,
+                            block_number as i64,
+                            Some(&format!("{:?}", tx_hash)),
+                        )
+                        .await?;
                 }
-                break;
             }
         }
 
-        // If we found liquidatable troves, clear cache so next call gets fresh data
-        if !liquidatable.is_empty() {
-            info!(
-                "Found {} liquidatable troves - clearing cache for next iteration",
-                liquidatable.len()
-            );
-          
-            self.memory_cache.clear_memory();
-           let _ = self.store.close_troves(&liquidatable).await;
-            self.executor.execute(liquidatable.clone()).await?;
-        }
-
         let end_time = std::time::Instant::now();
         let duration = end_time.duration_since(start_time);
         info!("🔍 Liquidation opportunities check took {:?}", duration);
@@ -252,7 +573,7 @@ impl LiquityStrategy {
     }
 
     /// Get fresh oracle price (ETH/USD)
-    async fn get_oracle_price(&self) -> Result<Uint<256, 4>> {
+    pub async fn get_oracle_price(&self) -> Result<Uint<256, 4>> {
         let price_feed = AggregatePriceFeed::new(self.oracle, &*self.provider);
         let price_i256 = price_feed.latestAnswer().call().await?;
 
@@ -264,11 +585,42 @@ impl LiquityStrategy {
 
         // Convert from 8 decimals to 18 decimals by multiplying by 10^10
 
+        let updated_at = price_feed.latestTimestamp().call().await?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        metrics::ORACLE_PRICE.set(units_to_f64(&price.to_string()));
+        metrics::ORACLE_AGE.set(now.saturating_sub(updated_at.to::<u64>()) as i64);
+        metrics::ORACLE_LAST_READ.set(now as i64);
+
         Ok(price)
     }
 
+    /// ICR of a stored trove at `block_timestamp`, with interest accrued since its last update.
+    /// `None` for troves with no collateral or debt, or unparseable values.
+    pub fn trove_icr(
+        trove: &Trove,
+        block_timestamp: u64,
+        price: Uint<256, 4>,
+    ) -> Option<Uint<256, 4>> {
+        let coll = Some(trove.collateral()).filter(|c| !c.is_zero())?;
+        let debt = Some(trove.debt()).filter(|d| !d.is_zero())?;
+        let interest_rate = trove.interest_rate();
+
+        Some(Self::calculate_full_icr(
+            debt,
+            coll,
+            interest_rate,
+            block_timestamp,
+            trove.last_updated_at as u64,
+            price,
+        ))
+    }
+
+    /// `TroveManager.getCurrentICR` for a trove without redistribution gains. Where the contract
+    /// would revert on overflow this saturates instead, and zero debt is an infinite ICR.
     pub fn calculate_full_icr(
-        &self,
         debt: Uint<256, 4>,
         coll: Uint<256, 4>,
         interest_rate: Uint<256, 4>,
@@ -284,31 +636,245 @@ impl LiquityStrategy {
 
         let accrued_interest = Self::calc_interest(weighted_recorded_debt, period_u256);
 
-        let entire_debt = debt + accrued_interest;
+        let entire_debt = debt.saturating_add(accrued_interest);
+        if entire_debt.is_zero() {
+            return Uint::MAX;
+        }
 
         // entireColl = coll + redistCollGain
         let entire_coll = coll;
 
-        (entire_coll * price) / entire_debt
+        match entire_coll.checked_mul(price) {
+            Some(value) => value / entire_debt,
+            None => U256::saturating_from(
+                U512::from(entire_coll) * U512::from(price) / U512::from(entire_debt),
+            ),
+        }
     }
 
+    /// `TroveManager._calcInterest`: interest accrued on `weighted_debt` (debt times rate) over
+    /// `period` seconds
     pub fn calc_interest(weighted_debt: Uint<256, 4>, period: Uint<256, 4>) -> Uint<256, 4> {
         let num = weighted_debt.saturating_mul(period);
-        let after_year = num / Uint::from(ONE_YEAR) / Uint::from(DECIMAL_PRECISION);
-        after_year
+        num / Uint::from(ONE_YEAR) / Uint::from(DECIMAL_PRECISION)
+    }
+
+    /// Lowest price (18 decimals) at which a trove's ICR is not below `mcr` once `accrual_secs`
+    /// of interest has accrued on its recorded debt: the trove is liquidatable exactly when the
+    /// price is below it
+    pub fn calculate_liquidation_price(
+        debt: Uint<256, 4>,
+        coll: Uint<256, 4>,
+        interest_rate: Uint<256, 4>,
+        mcr: Uint<256, 4>,
+        accrual_secs: u64,
+    ) -> Uint<256, 4> {
+        if debt.is_zero() {
+            return Uint::ZERO;
+        }
+        if coll.is_zero() {
+            return Uint::MAX;
+        }
+
+        let accrued_interest =
+            Self::calc_interest(debt.saturating_mul(interest_rate), U256::from(accrual_secs));
+        let entire_debt = debt.saturating_add(accrued_interest);
+
+        mcr.saturating_mul(entire_debt).div_ceil(coll)
+    }
+
+    /// Liquidation price valid until `now` plus the horizon, and that horizon timestamp
+    fn liquidation_price_entry(
+        &self,
+        debt: Uint<256, 4>,
+        coll: Uint<256, 4>,
+        interest_rate: Uint<256, 4>,
+        last_updated_at: u64,
+        now: u64,
+    ) -> (Uint<256, 4>, i64) {
+        let until = now + self.liquidation_price_horizon_secs;
+        let liquidation_price = Self::calculate_liquidation_price(
+            debt,
+            coll,
+            interest_rate,
+            self.mcr,
+            until.saturating_sub(last_updated_at),
+        );
+        (liquidation_price, until as i64)
+    }
+
+    /// Projected timestamp at which interest alone makes `trove` liquidatable at `price`
+    pub fn project_trove(&self, trove: &Trove, price: Uint<256, 4>) -> Option<u64> {
+        project_liquidation_time(
+            trove.debt(),
+            trove.collateral(),
+            trove.interest_rate(),
+            self.mcr,
+            price,
+            trove.last_updated_at as u64,
+        )
+    }
+
+    /// Project every active trove at `price` and schedule its re-check; returns how many were
+    /// scheduled
+    pub async fn project_active_troves(&self, price: Uint<256, 4>) -> Result<usize> {
+        self.memory_cache.ensure_loaded(&self.store).await?;
+
+        let mut scheduled = 0;
+        for trove in self.memory_cache.all() {
+            let Ok(trove_id) = Uint::<256, 4>::from_str(&trove.trove_id) else {
+                continue;
+            };
+            match self.project_trove(&trove, price) {
+                Some(at) => {
+                    self.rechecks.schedule(trove_id, at).await;
+                    scheduled += 1;
+                }
+                None => self.rechecks.cancel(trove_id).await,
+            }
+        }
+        Ok(scheduled)
+    }
+
+    async fn schedule_recheck(
+        &self,
+        trove_id: Uint<256, 4>,
+        debt: Uint<256, 4>,
+        coll: Uint<256, 4>,
+        interest_rate: Uint<256, 4>,
+        last_updated_at: u64,
+        price: Uint<256, 4>,
+    ) {
+        match project_liquidation_time(debt, coll, interest_rate, self.mcr, price, last_updated_at)
+        {
+            Some(at) => self.rechecks.schedule(trove_id, at).await,
+            None => self.rechecks.cancel(trove_id).await,
+        }
+    }
+
+    /// Re-run the projection pass when it is stale or the price has moved
+    async fn update_projections(&self, block_number: u64, price: Uint<256, 4>) -> Result<()> {
+        let last = *self.last_projection.read().await;
+        let stale = match last {
+            None => true,
+            Some((last_block, peak_price)) => {
+                block_number.saturating_sub(last_block) >= PROJECTION_INTERVAL_BLOCKS
+                    || peak_price.saturating_sub(price) * Uint::from(10_000u64)
+                        >= peak_price * Uint::from(PROJECTION_PRICE_DROP_BPS)
+            }
+        };
+        if !stale {
+            // Troves re-projected from here on use this price, so later drops count from it
+            if let Some((last_block, peak_price)) = last
+                && price > peak_price
+            {
+                *self.last_projection.write().await = Some((last_block, price));
+            }
+            return Ok(());
+        }
+
+        let scheduled = self.project_active_troves(price).await?;
+        *self.last_projection.write().await = Some((block_number, price));
+
+        match self.rechecks.next_due().await {
+            Some((at, count)) => info!(
+                "⏰ Projected {} troves at price {} - next interest re-check at {} ({} troves)",
+                scheduled, price, at, count
+            ),
+            None => info!("⏰ Projected {} troves at price {} - no re-checks pending", scheduled, price),
+        }
+        Ok(())
+    }
+
+    /// Resolve the update timestamp of troves stored before timestamps were tracked, so their
+    /// interest accrues from their last update rather than the epoch. Run once at startup; their
+    /// liquidation prices are expired and recomputed on the next block.
+    pub async fn backfill_update_timestamps(&self) -> Result<usize> {
+        self.memory_cache.ensure_loaded(&self.store).await?;
+        let legacy: Vec<Trove> = self
+            .memory_cache
+            .all()
+            .into_iter()
+            .filter(|trove| trove.last_updated_at == 0)
+            .collect();
+
+        let mut timestamps: HashMap<u64, u64> = HashMap::new();
+        for trove in &legacy {
+            let block_number = trove.last_updated as u64;
+            let updated_at = match timestamps.get(&block_number) {
+                Some(&timestamp) => timestamp,
+                None => {
+                    let timestamp = block_timestamp_of(&self.provider, block_number).await?;
+                    timestamps.insert(block_number, timestamp);
+                    timestamp
+                }
+            };
+            self.memory_cache
+                .set_liquidation_price(
+                    &self.store,
+                    &trove.trove_id,
+                    updated_at as i64,
+                    trove.liquidation_price.0,
+                    0,
+                )
+                .await?;
+        }
+
+        if !legacy.is_empty() {
+            info!("🕰️ Backfilled update timestamps for {} troves", legacy.len());
+        }
+        Ok(legacy.len())
+    }
+
+    /// Recompute liquidation prices whose accrual horizon has passed by `block_timestamp`
+    async fn refresh_liquidation_prices(&self, block_timestamp: u64) -> Result<usize> {
+        let expired =
+            self.memory_cache.expired_liquidation_prices(&self.store, block_timestamp as i64).await?;
+
+        for trove in &expired {
+            let debt = trove.debt();
+            let coll = trove.collateral();
+            let interest_rate = trove.interest_rate();
+
+            let last_updated_at = trove.last_updated_at as u64;
+
+            let (liquidation_price, until) = self.liquidation_price_entry(
+                debt,
+                coll,
+                interest_rate,
+                last_updated_at,
+                block_timestamp,
+            );
+            self.memory_cache
+                .set_liquidation_price(
+                    &self.store,
+                    &trove.trove_id,
+                    last_updated_at as i64,
+                    liquidation_price,
+                    until,
+                )
+                .await?;
+        }
+
+        let refreshed = expired.len();
+        if refreshed > 0 {
+            info!("🎯 Refreshed liquidation prices for {} troves", refreshed);
+        }
+        Ok(refreshed)
     }
 }
 
 #[async_trait::async_trait]
 impl Strategy<Log> for LiquityStrategy {
     async fn execute(&self, log: &Log) -> Result<()> {
-        if log.address() == self.trove_manager {
-            if let Some(event) = decode_event_log(log) {
-                // Custom decoder function
-                self.process_trove_event(&event, log.block_number.unwrap()).await?;
-            }
+        if log.address() == self.trove_manager
+            && let Some(event) = decode_event_log(log)
+        {
+            // Custom decoder function
+            self.process_trove_event(&event, log).await?;
         }
         self.store.set_last_block(log.block_number.unwrap() as i64).await?;
+        metrics::set_processed_block(log.block_number.unwrap());
         Ok(())
     }
 
@@ -329,16 +895,33 @@ impl Strategy<u64> for LiquityStrategy {
         let logs = self.provider.get_logs(&filter).await?;
 
         for log in logs {
-            if log.address() == self.trove_manager {
-                if let Some(event) = decode_event_log(&log) {
-                    self.process_trove_event(&event, log.block_number.unwrap()).await?;
-                    self.memory_cache.clear_memory();
-                }
+            if log.address() == self.trove_manager
+                && let Some(event) = decode_event_log(&log)
+            {
+                self.process_trove_event(&event, &log).await?;
             }
         }
         self.store.set_last_block(*block_number as i64).await?;
+        metrics::set_processed_block(*block_number);
 
-        let _ = self.check_for_liquidation_opportunities(*block_number).await?;
+        let block_timestamp = block_timestamp_of(&self.provider, *block_number).await?;
+        let _ = self.check_for_liquidation_opportunities(*block_number, block_timestamp).await?;
+
+        if block_number.is_multiple_of(CROSS_CHECK_INTERVAL_BLOCKS) {
+            self.cross_check_sorted_troves(*block_number).await?;
+        }
+        if block_number.is_multiple_of(CACHE_REPORT_INTERVAL_BLOCKS) {
+            let cache = self.memory_cache.cache_info().await;
+            info!(
+                "🗂️ Trove book - {} troves ({} price-indexed), hits: {}, misses: {}, last reload: {}ms, age: {}s",
+                cache.trove_count,
+                cache.indexed_by_price,
+                cache.hits,
+                cache.misses,
+                cache.last_reload_ms,
+                cache.age_seconds
+            );
+        }
         // }
         // let end_time = std::time::Instant::now();
         // let duration = end_time.duration_since(start_time);
@@ -1,5 +1,14 @@
+pub mod backtest;
+pub mod branch_monitor;
+pub mod competitor_analytics;
+pub mod liquidation_recorder;
+#[allow(clippy::module_inception)]
 pub mod liquity;
 pub mod liquity_strategy;
 pub mod liquity_exexcution;
+pub mod recheck_scheduler;
+pub mod reconciler;
+pub mod redemption_strategy;
+pub mod sorted_troves;
 pub mod trove_memory_cache;
 
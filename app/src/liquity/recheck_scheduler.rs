@@ -0,0 +1,116 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
+use alloy::primitives::U256;
+use tokio::sync::RwLock;
+
+use crate::liquity::liquity_strategy::{DECIMAL_PRECISION, LiquityStrategy, ONE_YEAR};
+
+/// Timestamp at which interest accrued since `last_updated_at` pushes a trove's ICR below `mcr`
+/// at a fixed `price`. Returns `last_updated_at` if the trove is already below MCR and `None` if
+/// it never crosses (no debt, or no interest to accrue).
+pub fn project_liquidation_time(
+    debt: U256,
+    coll: U256,
+    interest_rate: U256,
+    mcr: U256,
+    price: U256,
+    last_updated_at: u64,
+) -> Option<u64> {
+    if debt.is_zero() || mcr.is_zero() {
+        return None;
+    }
+
+    // ICR drops below MCR once the entire debt exceeds coll * price / mcr
+    let max_safe_debt = coll.saturating_mul(price) / mcr;
+    if debt > max_safe_debt {
+        return Some(last_updated_at);
+    }
+    if interest_rate.is_zero() {
+        return None;
+    }
+
+    // `calc_interest` floors weighted_debt * t / ONE_YEAR / DECIMAL_PRECISION; solve for the
+    // first t at which it covers the gap, rounding up
+    let gap = max_safe_debt - debt + U256::from(1);
+    let weighted_debt = debt.saturating_mul(interest_rate);
+    let secs = gap
+        .saturating_mul(U256::from(ONE_YEAR))
+        .saturating_mul(U256::from(DECIMAL_PRECISION))
+        .div_ceil(weighted_debt);
+    // Saturated products never cross in a representable time
+    if LiquityStrategy::calc_interest(weighted_debt, secs) < gap {
+        return None;
+    }
+
+    u64::try_from(secs).ok().and_then(|secs| last_updated_at.checked_add(secs))
+}
+
+#[derive(Debug, Default)]
+struct Schedule {
+    by_time: BTreeMap<u64, HashSet<U256>>,
+    by_trove: HashMap<U256, u64>,
+}
+
+impl Schedule {
+    fn remove(&mut self, trove_id: U256) {
+        let Some(at) = self.by_trove.remove(&trove_id) else {
+            return;
+        };
+        if let Some(troves) = self.by_time.get_mut(&at) {
+            troves.remove(&trove_id);
+            if troves.is_empty() {
+                self.by_time.remove(&at);
+            }
+        }
+    }
+}
+
+/// Troves to re-check at the timestamp interest is projected to make them liquidatable.
+/// Each trove has at most one pending re-check; scheduling it again replaces the old one.
+#[derive(Clone, Default)]
+pub struct RecheckScheduler {
+    schedule: Arc<RwLock<Schedule>>,
+}
+
+impl RecheckScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn schedule(&self, trove_id: U256, at: u64) {
+        let mut schedule = self.schedule.write().await;
+        schedule.remove(trove_id);
+        schedule.by_time.entry(at).or_default().insert(trove_id);
+        schedule.by_trove.insert(trove_id, at);
+    }
+
+    pub async fn cancel(&self, trove_id: U256) {
+        self.schedule.write().await.remove(trove_id);
+    }
+
+    /// Remove and return every trove whose re-check is due at `now`
+    pub async fn take_due(&self, now: u64) -> Vec<U256> {
+        let mut schedule = self.schedule.write().await;
+        let later = schedule.by_time.split_off(&(now + 1));
+        let due = std::mem::replace(&mut schedule.by_time, later);
+
+        let troves: Vec<U256> = due.into_values().flatten().collect();
+        for trove_id in &troves {
+            schedule.by_trove.remove(trove_id);
+        }
+        troves
+    }
+
+    /// Earliest pending re-check, if any
+    pub async fn next_due(&self) -> Option<(u64, usize)> {
+        let schedule = self.schedule.read().await;
+        schedule.by_time.first_key_value().map(|(at, troves)| (*at, troves.len()))
+    }
+
+    pub async fn size(&self) -> usize {
+        self.schedule.read().await.by_trove.len()
+    }
+}
@@ -0,0 +1,227 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use eyre::Result;
+use log::{error, info, warn};
+use tokio::{sync::RwLock, time::sleep};
+
+use crate::{
+    db::{
+        DatabaseStore,
+        store::{Trove, TroveStatus},
+    },
+    liquity::{
+        liquity::TroveManager, liquity_strategy::StrategyProvider,
+        trove_memory_cache::TroveMemoryCache,
+    },
+    metrics,
+};
+
+/// Number of troves with the largest debt drift kept in each summary
+const WORST_OFFENDERS: usize = 10;
+
+/// A stored trove whose values disagree with `TroveManager.Troves(id)`
+#[derive(Debug, Clone)]
+pub struct DriftEntry {
+    pub trove_id: String,
+    pub debt_delta: U256,
+    pub coll_delta: U256,
+}
+
+/// Result of one reconciliation pass
+#[derive(Debug, Clone, Default)]
+pub struct DriftSummary {
+    pub checked: usize,
+    pub collateral: usize,
+    pub debt: usize,
+    pub interest_rate: usize,
+    pub status: usize,
+    pub repaired: usize,
+    pub errors: usize,
+    pub worst_offenders: Vec<DriftEntry>,
+}
+
+impl DriftSummary {
+    fn record_offender(&mut self, entry: DriftEntry) {
+        self.worst_offenders.push(entry);
+        self.worst_offenders.sort_by_key(|entry| std::cmp::Reverse(entry.debt_delta));
+        self.worst_offenders.truncate(WORST_OFFENDERS);
+    }
+
+    pub fn has_drift(&self) -> bool {
+        self.collateral + self.debt + self.interest_rate + self.status > 0
+    }
+}
+
+/// Background task that sweeps stored open troves, and troves we closed ahead of our own
+/// liquidation, compares them with chain state and repairs
+/// any drift left behind by missed or skipped logs
+#[derive(Clone)]
+pub struct TroveReconciler {
+    trove_manager: Address,
+    store: Arc<dyn DatabaseStore>,
+    provider: Arc<StrategyProvider>,
+    interval: Duration,
+    batch_size: i64,
+    cursor: Arc<RwLock<String>>,
+    memory_cache: Option<TroveMemoryCache>,
+}
+
+impl TroveReconciler {
+    pub fn new(
+        trove_manager: Address,
+        store: Arc<dyn DatabaseStore>,
+        provider: Arc<StrategyProvider>,
+        interval_secs: u64,
+        batch_size: i64,
+    ) -> Self {
+        Self {
+            trove_manager,
+            store,
+            provider,
+            interval: Duration::from_secs(interval_secs),
+            batch_size,
+            cursor: Arc::new(RwLock::new(String::new())),
+            memory_cache: None,
+        }
+    }
+
+    /// Write repairs through the strategy's in-memory trove book as well as the DB
+    pub fn set_memory_cache(&mut self, memory_cache: TroveMemoryCache) {
+        self.memory_cache = Some(memory_cache);
+    }
+
+    /// Run reconciliation passes forever, one batch every interval
+    pub async fn run(&self) {
+        info!(
+            "🧮 Trove reconciler started (batch: {}, interval: {:?})",
+            self.batch_size, self.interval
+        );
+
+        loop {
+            match self.reconcile_batch().await {
+                Ok(summary) => Self::report(&summary),
+                Err(e) => error!("❌ Trove reconciliation failed: {}", e),
+            }
+            sleep(self.interval).await;
+        }
+    }
+
+    /// Reconcile the next batch of troves, wrapping around once the sweep reaches the end
+    pub async fn reconcile_batch(&self) -> Result<DriftSummary> {
+        let cursor = self.cursor.read().await.clone();
+        let troves = self.store.get_reconcilable_troves_after(&cursor, self.batch_size).await?;
+
+        let next_cursor = match troves.last() {
+            Some(trove) if troves.len() as i64 == self.batch_size => trove.trove_id.clone(),
+            _ => String::new(),
+        };
+        *self.cursor.write().await = next_cursor;
+
+        self.reconcile_troves(&troves).await
+    }
+
+    /// Compare the given troves against `TroveManager.Troves(id)` and repair mismatches
+    pub async fn reconcile_troves(&self, troves: &[Trove]) -> Result<DriftSummary> {
+        let mut summary = DriftSummary::default();
+        let block_number = self.provider.get_block_number().await?;
+        let trove_manager = TroveManager::new(self.trove_manager, &*self.provider);
+
+        for trove in troves {
+            let Ok(trove_id) = U256::from_str(&trove.trove_id) else {
+                summary.errors += 1;
+                continue;
+            };
+
+            let on_chain = match trove_manager.Troves(trove_id).call().await {
+                Ok(state) => state,
+                Err(e) => {
+                    warn!("⚠️ Failed to read trove {} from chain: {}", trove.trove_id, e);
+                    summary.errors += 1;
+                    continue;
+                }
+            };
+            summary.checked += 1;
+
+            let stored_coll = trove.collateral();
+            let stored_debt = trove.debt();
+            let stored_rate = trove.interest_rate();
+            let chain_status = TroveStatus::from_chain(on_chain.status);
+
+            let coll_drift = stored_coll != on_chain.coll;
+            let debt_drift = stored_debt != on_chain.debt;
+            let rate_drift = stored_rate != on_chain.annualInterestRate;
+            let status_drift = trove.status != chain_status.as_str();
+
+            if !(coll_drift || debt_drift || rate_drift || status_drift) {
+                continue;
+            }
+
+            summary.collateral += coll_drift as usize;
+            summary.debt += debt_drift as usize;
+            summary.interest_rate += rate_drift as usize;
+            summary.status += status_drift as usize;
+            summary.record_offender(DriftEntry {
+                trove_id: trove.trove_id.clone(),
+                debt_delta: stored_debt.abs_diff(on_chain.debt),
+                coll_delta: stored_coll.abs_diff(on_chain.coll),
+            });
+
+            let repaired = Trove {
+                trove_id: trove.trove_id.clone(),
+                collateral: on_chain.coll.into(),
+                debt: on_chain.debt.into(),
+                icr_key: Trove::icr_key(on_chain.coll, on_chain.debt),
+                interest_rate: on_chain.annualInterestRate.into(),
+                status: chain_status.as_str().to_string(),
+                last_updated: block_number as i64,
+                last_updated_at: on_chain.lastDebtUpdateTime as i64,
+                // Expire the liquidation price so the strategy recomputes it from the repaired state
+                liquidation_price: trove.liquidation_price,
+                liquidation_price_until: 0,
+            };
+
+            let result = match &self.memory_cache {
+                Some(memory_cache) => memory_cache.upsert(&self.store, &repaired).await,
+                None => self.store.upsert_trove(&repaired).await,
+            };
+            match result {
+                Ok(()) => summary.repaired += 1,
+                Err(e) => {
+                    error!("❌ Failed to repair trove {}: {}", trove.trove_id, e);
+                    summary.errors += 1;
+                }
+            }
+        }
+
+        metrics::record_reconciliation(&summary);
+        Ok(summary)
+    }
+
+    pub fn report(summary: &DriftSummary) {
+        if !summary.has_drift() {
+            info!("🧮 Reconciled {} troves - no drift", summary.checked);
+            return;
+        }
+
+        warn!(
+            "🧮 Reconciled {} troves - drift: collateral={}, debt={}, interest_rate={}, status={} (repaired: {}, errors: {})",
+            summary.checked,
+            summary.collateral,
+            summary.debt,
+            summary.interest_rate,
+            summary.status,
+            summary.repaired,
+            summary.errors
+        );
+        for entry in &summary.worst_offenders {
+            warn!(
+                "🧮   trove {} - debt delta: {}, coll delta: {}",
+                entry.trove_id, entry.debt_delta, entry.coll_delta
+            );
+        }
+    }
+}
@@ -0,0 +1,500 @@
+use std::{str::FromStr, sync::Arc};
+
+use alloy::{
+    primitives::{Address, Bytes, I256, U256},
+    sol,
+    sol_types::SolCall,
+};
+use eyre::Result;
+use log::{info, warn};
+use tokio::sync::RwLock;
+
+use crate::{
+    PriceFeed,
+    config::RedemptionConfig,
+    db::DatabaseStore,
+    liquity::{
+        branch_monitor::{BranchHealth, BranchMode},
+        liquidation_recorder::signed_diff,
+        liquity_exexcution::LiquityExecutor,
+        liquity_strategy::{AggregatePriceFeed, StrategyProvider, TroveManager},
+    },
+    strategy::Strategy,
+};
+
+sol!(
+    #[sol(rpc)]
+    interface ICollateralRegistry {
+        function getRedemptionRateForRedeemedAmount(uint256 _redeemAmount) external view returns (uint256);
+        function redeemCollateral(uint256 _boldAmount, uint256 _maxIterations, uint256 _maxFeePercentage) external;
+        function totalCollaterals() external view returns (uint256);
+        function getTroveManager(uint256 _index) external view returns (address);
+    }
+);
+
+static DECIMAL_PRECISION: u128 = 1_000_000_000_000_000_000u128;
+/// Protocol minimum redemption fee (0.5%), used when no `CollateralRegistry` is configured
+const REDEMPTION_FEE_FLOOR: u128 = 5_000_000_000_000_000u128;
+/// Collateral bonus paid by urgent redemptions on a shut down branch (1%)
+const URGENT_REDEMPTION_BONUS: u128 = 10_000_000_000_000_000u128;
+const BPS: u64 = 10_000;
+
+/// One branch's part in a `redeemCollateral` call, as `CollateralRegistry` sizes it
+#[derive(Debug, Clone, Copy)]
+pub struct BranchPortion {
+    /// Unbacked debt (or total debt, when no branch is unbacked); 0 for unredeemable branches
+    pub unbacked: U256,
+    /// Redemption price of the branch collateral
+    pub price: U256,
+    /// Whether this is the branch whose troves we track
+    pub is_ours: bool,
+}
+
+/// Collateral and value received from a `redeemCollateral` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedemptionEstimate {
+    /// BOLD redeemed against our branch's troves
+    pub branch_bold: U256,
+    /// Collateral received from our branch
+    pub collateral_received: U256,
+    /// Value of the collateral received from every branch
+    pub collateral_value_usd: U256,
+}
+
+/// Estimated outcome of redeeming BOLD against the lowest-interest troves
+#[derive(Debug, Clone)]
+pub struct RedemptionOpportunity {
+    pub bold_amount: U256,
+    pub branch_bold: U256,
+    pub trove_ids: Vec<String>,
+    pub fee_rate: U256,
+    pub bold_price: U256,
+    pub redemption_price: U256,
+    pub collateral_received: U256,
+    pub collateral_value_usd: U256,
+    pub cost_usd: U256,
+    pub profit_usd: I256,
+}
+
+/// An `urgentRedemption` call prepared for a shut down branch
+#[derive(Debug, Clone)]
+pub struct UrgentRedemption {
+    pub bold_amount: U256,
+    pub trove_ids: Vec<U256>,
+    pub price: U256,
+    pub expected_collateral: U256,
+    pub min_collateral: U256,
+    pub collateral_value_usd: U256,
+    pub cost_usd: U256,
+    pub profit_usd: I256,
+}
+
+/// Strategy that watches for BOLD trading below peg and estimates whether redeeming it against the
+/// lowest-interest troves is profitable
+#[derive(Clone)]
+pub struct RedemptionStrategy {
+    name: String,
+    store: Arc<dyn DatabaseStore>,
+    provider: Arc<StrategyProvider>,
+    executor: LiquityExecutor,
+    trove_manager: Address,
+    price_feed: Address,
+    oracle: Address,
+    branch_health: Option<Arc<RwLock<BranchHealth>>>,
+    bold_price_oracle: Option<Address>,
+    collateral_registry: Option<Address>,
+    max_redemption_bold: U256,
+    min_profit_usd: U256,
+    max_iterations: u64,
+    urgent_min_collateral_bps: u64,
+    max_fee_headroom_bps: u64,
+    execute_enabled: bool,
+}
+
+impl RedemptionStrategy {
+    pub fn new(
+        store: Arc<dyn DatabaseStore>,
+        provider: Arc<StrategyProvider>,
+        executor: LiquityExecutor,
+        trove_manager: Address,
+        price_feed: Address,
+        oracle: Address,
+        config: &RedemptionConfig,
+    ) -> Self {
+        Self {
+            name: "RedemptionStrategy".to_string(),
+            store,
+            provider,
+            executor,
+            trove_manager,
+            price_feed,
+            oracle,
+            branch_health: None,
+            bold_price_oracle: config.bold_price_oracle,
+            collateral_registry: config.collateral_registry,
+            max_redemption_bold: U256::from(config.max_redemption_bold),
+            min_profit_usd: U256::from(config.min_profit_usd),
+            max_iterations: config.max_iterations,
+            urgent_min_collateral_bps: config.urgent_min_collateral_bps,
+            max_fee_headroom_bps: config.max_fee_headroom_bps,
+            execute_enabled: config.execute,
+        }
+    }
+
+    /// Follow the branch mode published by a `BranchMonitor`
+    pub fn set_branch_health(&mut self, health: Arc<RwLock<BranchHealth>>) {
+        self.branch_health = Some(health);
+    }
+
+    async fn branch_mode(&self) -> BranchMode {
+        match &self.branch_health {
+            Some(health) => health.read().await.mode,
+            None => BranchMode::Normal,
+        }
+    }
+
+    /// Select troves and size an `urgentRedemption` on a shut down branch. Urgent redemptions
+    /// ignore interest-rate order, so the largest troves are used to keep the id list short.
+    pub async fn find_urgent_redemption(&self) -> Result<Option<UrgentRedemption>> {
+        let precision = U256::from(DECIMAL_PRECISION);
+
+        let price =
+            PriceFeed::new(self.price_feed, &*self.provider).fetchRedemptionPrice().call().await?._0;
+        if price.is_zero() {
+            return Err(eyre::eyre!("Invalid redemption price"));
+        }
+
+        let troves = self.store.get_troves_by_debt(self.max_iterations as i64).await?;
+        let mut bold_amount = U256::ZERO;
+        let mut expected_collateral = U256::ZERO;
+        let mut trove_ids = Vec::new();
+        for trove in troves {
+            let remaining = self.max_redemption_bold.saturating_sub(bold_amount);
+            if remaining.is_zero() {
+                break;
+            }
+            let Ok(trove_id) = U256::from_str(&trove.trove_id) else {
+                continue;
+            };
+            let (debt, coll) = (trove.debt(), trove.collateral());
+            if debt.is_zero() {
+                continue;
+            }
+
+            // Each trove pays out at most its own collateral
+            let redeemed = debt.min(remaining);
+            let collateral =
+                (redeemed * (precision + U256::from(URGENT_REDEMPTION_BONUS)) / price).min(coll);
+            bold_amount += redeemed;
+            expected_collateral += collateral;
+            trove_ids.push(trove_id);
+        }
+        if bold_amount.is_zero() {
+            return Ok(None);
+        }
+
+        let bold_price = self.bold_price().await?.unwrap_or(precision);
+        let collateral_value_usd = expected_collateral * price / precision;
+        let cost_usd = bold_amount * bold_price / precision;
+
+        Ok(Some(UrgentRedemption {
+            bold_amount,
+            trove_ids,
+            price,
+            expected_collateral,
+            min_collateral: expected_collateral * U256::from(self.urgent_min_collateral_bps)
+                / U256::from(BPS),
+            collateral_value_usd,
+            cost_usd,
+            profit_usd: signed_diff(collateral_value_usd, cost_usd),
+        }))
+    }
+
+    /// Estimate the redemption available right now, if BOLD is below peg. `redeemCollateral`
+    /// splits the BOLD across branches in proportion to their unbacked debt, so the amount is
+    /// sized for our branch's share to fit the troves we walk, and collateral from other
+    /// branches is valued at their redemption price.
+    pub async fn find_opportunity(&self) -> Result<Option<RedemptionOpportunity>> {
+        let precision = U256::from(DECIMAL_PRECISION);
+
+        let Some(bold_price) = self.bold_price().await? else {
+            return Ok(None);
+        };
+        if bold_price >= precision {
+            return Ok(None);
+        }
+
+        let branches = self.branch_portions().await?;
+        let total_unbacked = branches.iter().fold(U256::ZERO, |sum, branch| sum + branch.unbacked);
+        let Some(ours) = branches.iter().find(|branch| branch.is_ours) else {
+            return Ok(None);
+        };
+        if ours.unbacked.is_zero() || ours.price.is_zero() {
+            return Ok(None);
+        }
+        let max_branch_bold = self.max_redemption_bold * ours.unbacked / total_unbacked;
+
+        // Redemptions hit troves in order of increasing interest rate
+        let troves = self.store.get_troves_by_interest_rate(self.max_iterations as i64).await?;
+        let mut branch_capacity = U256::ZERO;
+        let mut trove_ids = Vec::new();
+        for trove in troves {
+            if branch_capacity >= max_branch_bold {
+                break;
+            }
+            let debt = trove.debt();
+            if debt.is_zero() {
+                continue;
+            }
+            branch_capacity += debt;
+            trove_ids.push(trove.trove_id);
+        }
+        let bold_amount =
+            self.max_redemption_bold.min(branch_capacity * total_unbacked / ours.unbacked);
+        if bold_amount.is_zero() {
+            return Ok(None);
+        }
+
+        let fee_rate = self.redemption_rate(bold_amount).await?;
+        let collateral_price = self.read_feed(self.oracle).await?;
+        let estimate = estimate_redemption(bold_amount, fee_rate, &branches, collateral_price);
+        let cost_usd = bold_amount * bold_price / precision;
+
+        Ok(Some(RedemptionOpportunity {
+            bold_amount,
+            branch_bold: estimate.branch_bold,
+            trove_ids,
+            fee_rate,
+            bold_price,
+            redemption_price: ours.price,
+            collateral_received: estimate.collateral_received,
+            collateral_value_usd: estimate.collateral_value_usd,
+            cost_usd,
+            profit_usd: signed_diff(estimate.collateral_value_usd, cost_usd),
+        }))
+    }
+
+    /// Every branch's part in a redemption, read the way `CollateralRegistry.redeemCollateral`
+    /// does. Without a registry, our branch takes the whole redemption.
+    async fn branch_portions(&self) -> Result<Vec<BranchPortion>> {
+        let Some(registry) = self.collateral_registry else {
+            let price = PriceFeed::new(self.price_feed, &*self.provider)
+                .fetchRedemptionPrice()
+                .call()
+                .await?
+                ._0;
+            return Ok(vec![BranchPortion { unbacked: U256::from(1), price, is_ours: true }]);
+        };
+
+        let registry = ICollateralRegistry::new(registry, &*self.provider);
+        let total_collaterals: u64 = registry.totalCollaterals().call().await?.try_into()?;
+        let mut trove_managers = Vec::new();
+        let mut branches = Vec::new();
+        for index in 0..total_collaterals {
+            let address = registry.getTroveManager(U256::from(index)).call().await?;
+            let trove_manager = TroveManager::new(address, &*self.provider);
+            let portion = trove_manager.getUnbackedPortionPriceAndRedeemability().call().await?;
+            branches.push(BranchPortion {
+                unbacked: if portion._2 { portion._0 } else { U256::ZERO },
+                price: portion._1,
+                is_ours: address == self.trove_manager,
+            });
+            trove_managers.push((trove_manager, portion._2));
+        }
+
+        // With no unbacked debt anywhere, the registry splits by total branch debt
+        if branches.iter().all(|branch| branch.unbacked.is_zero()) {
+            for (branch, (trove_manager, redeemable)) in branches.iter_mut().zip(&trove_managers) {
+                if *redeemable {
+                    branch.unbacked = trove_manager.getEntireBranchDebt().call().await?;
+                }
+            }
+        }
+        Ok(branches)
+    }
+
+    /// Redemption fee rate (18 decimals) for redeeming `bold_amount`
+    async fn redemption_rate(&self, bold_amount: U256) -> Result<U256> {
+        match self.collateral_registry {
+            Some(registry) => {
+                let registry = ICollateralRegistry::new(registry, &*self.provider);
+                Ok(registry.getRedemptionRateForRedeemedAmount(bold_amount).call().await?)
+            }
+            None => Ok(U256::from(REDEMPTION_FEE_FLOOR)),
+        }
+    }
+
+    /// BOLD/USD price (18 decimals), when a BOLD feed is configured
+    async fn bold_price(&self) -> Result<Option<U256>> {
+        match self.bold_price_oracle {
+            Some(feed) => Ok(Some(self.read_feed(feed).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read a Chainlink-style feed and scale it from 8 to 18 decimals
+    async fn read_feed(&self, feed: Address) -> Result<U256> {
+        let answer = AggregatePriceFeed::new(feed, &*self.provider).latestAnswer().call().await?;
+        let answer =
+            U256::try_from(answer).map_err(|_| eyre::eyre!("Negative price from feed {:?}", feed))?;
+        Ok(answer * U256::from(10u64.pow(10)))
+    }
+
+    /// Redeem through `CollateralRegistry.redeemCollateral` via the `LiquidationExecutor`
+    async fn execute_redemption(&self, opportunity: &RedemptionOpportunity) -> Result<()> {
+        let Some(registry) = self.collateral_registry else {
+            return Err(eyre::eyre!("No CollateralRegistry configured for redemptions"));
+        };
+
+        let data = ICollateralRegistry::redeemCollateralCall {
+            _boldAmount: opportunity.bold_amount,
+            _maxIterations: U256::from(self.max_iterations),
+            _maxFeePercentage: max_fee_percentage(
+                opportunity.fee_rate,
+                self.max_fee_headroom_bps,
+            ),
+        }
+        .abi_encode();
+
+        if let Some(tx_hash) = self.executor.execute_call(registry, Bytes::from(data)).await? {
+            info!("💱 Redemption sent: {:?}", tx_hash);
+        }
+        Ok(())
+    }
+
+    /// Send `TroveManager.urgentRedemption` via the `LiquidationExecutor`
+    async fn execute_urgent_redemption(&self, redemption: &UrgentRedemption) -> Result<()> {
+        let data = TroveManager::urgentRedemptionCall {
+            _boldAmount: redemption.bold_amount,
+            _troveIds: redemption.trove_ids.clone(),
+            _minCollateral: redemption.min_collateral,
+        }
+        .abi_encode();
+
+        if let Some(tx_hash) = self.executor.execute_call(self.trove_manager, Bytes::from(data)).await? {
+            info!("🚨 Urgent redemption sent: {:?}", tx_hash);
+        }
+        Ok(())
+    }
+
+    async fn execute_urgent_mode(&self, block_number: u64) -> Result<()> {
+        let Some(redemption) = self.find_urgent_redemption().await? else {
+            return Ok(());
+        };
+
+        warn!(
+            "🚨 Block {} - urgent redemption: {} BOLD across {} troves, expected collateral {} (min {}), profit {}",
+            block_number,
+            redemption.bold_amount,
+            redemption.trove_ids.len(),
+            redemption.expected_collateral,
+            redemption.min_collateral,
+            redemption.profit_usd
+        );
+
+        if self.execute_enabled && redemption.profit_usd >= I256::from_raw(self.min_profit_usd) {
+            self.execute_urgent_redemption(&redemption).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Strategy<u64> for RedemptionStrategy {
+    async fn execute(&self, block_number: &u64) -> Result<()> {
+        if self.branch_mode().await == BranchMode::UrgentRedemption {
+            return self.execute_urgent_mode(*block_number).await;
+        }
+
+        let Some(opportunity) = self.find_opportunity().await? else {
+            return Ok(());
+        };
+
+        if opportunity.profit_usd < I256::from_raw(self.min_profit_usd) {
+            info!(
+                "💱 Block {} - BOLD at {}, redeeming {} BOLD not profitable (profit: {})",
+                block_number, opportunity.bold_price, opportunity.bold_amount, opportunity.profit_usd
+            );
+            return Ok(());
+        }
+
+        info!(
+            "💱 Block {} - profitable redemption: {} BOLD ({} on this branch across {} troves), fee rate {}, collateral {} worth {} for cost {}, profit {}",
+            block_number,
+            opportunity.bold_amount,
+            opportunity.branch_bold,
+            opportunity.trove_ids.len(),
+            opportunity.fee_rate,
+            opportunity.collateral_received,
+            opportunity.collateral_value_usd,
+            opportunity.cost_usd,
+            opportunity.profit_usd
+        );
+
+        if self.execute_enabled {
+            self.execute_redemption(&opportunity).await?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// BOLD each branch receives from a redemption of `bold_amount`, in proportion to its unbacked
+/// debt
+pub fn redemption_split(bold_amount: U256, branches: &[BranchPortion]) -> Vec<U256> {
+    let total_unbacked = branches.iter().fold(U256::ZERO, |sum, branch| sum + branch.unbacked);
+    branches
+        .iter()
+        .map(|branch| {
+            if total_unbacked.is_zero() {
+                U256::ZERO
+            } else {
+                bold_amount * branch.unbacked / total_unbacked
+            }
+        })
+        .collect()
+}
+
+/// Collateral received from redeeming `bold_amount` across `branches` at `fee_rate`. Our
+/// branch's collateral is valued at the oracle `collateral_price`, the others at their
+/// redemption price.
+pub fn estimate_redemption(
+    bold_amount: U256,
+    fee_rate: U256,
+    branches: &[BranchPortion],
+    collateral_price: U256,
+) -> RedemptionEstimate {
+    let precision = U256::from(DECIMAL_PRECISION);
+    let after_fee = precision - fee_rate.min(precision);
+
+    let mut estimate = RedemptionEstimate {
+        branch_bold: U256::ZERO,
+        collateral_received: U256::ZERO,
+        collateral_value_usd: U256::ZERO,
+    };
+    for (branch, bold) in branches.iter().zip(redemption_split(bold_amount, branches)) {
+        if bold.is_zero() || branch.price.is_zero() {
+            continue;
+        }
+        let collateral = bold * after_fee / branch.price;
+        if branch.is_ours {
+            estimate.branch_bold = bold;
+            estimate.collateral_received = collateral;
+            estimate.collateral_value_usd += collateral * collateral_price / precision;
+        } else {
+            estimate.collateral_value_usd += collateral * branch.price / precision;
+        }
+    }
+    estimate
+}
+
+/// `_maxFeePercentage` for a redemption quoted at `fee_rate`, leaving `headroom_bps` for the base
+/// rate to move before the transaction lands
+pub fn max_fee_percentage(fee_rate: U256, headroom_bps: u64) -> U256 {
+    let precision = U256::from(DECIMAL_PRECISION);
+    let headroom = precision * U256::from(headroom_bps) / U256::from(BPS);
+    (fee_rate + headroom).min(precision)
+}
@@ -0,0 +1,211 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use alloy::{primitives::{Address, U256}, sol};
+use eyre::Result;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    db::store::{Trove, TroveStatus},
+    liquity::liquity_strategy::StrategyProvider,
+};
+
+sol!(
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[sol(rpc)]
+    SortedTroves,
+    "../artifacts/ISortedTroves.sol/ISortedTroves.json"
+);
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Node {
+    prev: U256,
+    next: U256,
+}
+
+/// Local copy of the `SortedTroves` doubly linked list. Id zero is the list sentinel, as on-chain.
+#[derive(Debug, Default)]
+struct SortedList {
+    head: U256,
+    tail: U256,
+    nodes: HashMap<U256, Node>,
+}
+
+impl SortedList {
+    fn remove(&mut self, id: U256) {
+        let Some(node) = self.nodes.remove(&id) else {
+            return;
+        };
+        match self.nodes.get_mut(&node.prev) {
+            Some(prev) => prev.next = node.next,
+            None => self.head = node.next,
+        }
+        match self.nodes.get_mut(&node.next) {
+            Some(next) => next.prev = node.prev,
+            None => self.tail = node.prev,
+        }
+    }
+
+    /// Link `id` between `prev` and `next`; fails if either neighbour is unknown locally
+    fn insert(&mut self, id: U256, prev: U256, next: U256) -> bool {
+        let prev_known = prev.is_zero() || self.nodes.contains_key(&prev);
+        let next_known = next.is_zero() || self.nodes.contains_key(&next);
+        if !prev_known || !next_known {
+            return false;
+        }
+
+        match self.nodes.get_mut(&prev) {
+            Some(node) => node.next = id,
+            None => self.head = id,
+        }
+        match self.nodes.get_mut(&next) {
+            Some(node) => node.prev = id,
+            None => self.tail = id,
+        }
+        self.nodes.insert(id, Node { prev, next });
+        true
+    }
+
+    /// Ids from the last node backwards, the order `AutoLiquidator` walks the list in
+    fn ids_from_last(&self, limit: usize) -> Vec<U256> {
+        let mut ids = Vec::with_capacity(limit.min(self.nodes.len()));
+        let mut cursor = self.tail;
+        while !cursor.is_zero() && ids.len() < limit {
+            ids.push(cursor);
+            cursor = self.nodes.get(&cursor).map(|node| node.prev).unwrap_or_default();
+        }
+        ids
+    }
+}
+
+/// How the mirrored list disagrees with the DB's open troves. SortedTroves orders by interest
+/// rate rather than ICR, so the orderings are compared through the stored rates.
+#[derive(Debug, Clone, Default)]
+pub struct SortedTrovesCrossCheck {
+    pub listed: usize,
+    /// Active troves in the DB; zombies are unlisted on-chain and not expected in the list
+    pub active: usize,
+    pub missing_from_list: Vec<String>,
+    pub missing_from_db: Vec<String>,
+    /// Adjacent listed troves, walked from the last node, whose stored rates decrease
+    pub rate_inversions: usize,
+}
+
+/// Compare `listed` ids, in the order walked from the last node, against the DB's open troves
+pub fn cross_check(listed: &[U256], open_troves: &[Trove]) -> SortedTrovesCrossCheck {
+    let active: HashMap<String, &Trove> = open_troves
+        .iter()
+        .filter(|trove| TroveStatus::parse(&trove.status) == Some(TroveStatus::Active))
+        .map(|trove| (trove.trove_id.clone(), trove))
+        .collect();
+    let listed_ids: Vec<String> = listed.iter().map(|id| id.to_string()).collect();
+    let listed_set: HashSet<&String> = listed_ids.iter().collect();
+
+    let mut missing_from_list: Vec<String> =
+        active.keys().filter(|id| !listed_set.contains(id)).cloned().collect();
+    missing_from_list.sort();
+    let missing_from_db =
+        listed_ids.iter().filter(|id| !active.contains_key(*id)).cloned().collect();
+
+    let rates: Vec<U256> = listed_ids
+        .iter()
+        .filter_map(|id| active.get(id))
+        .map(|trove| trove.interest_rate())
+        .collect();
+    let rate_inversions = rates.windows(2).filter(|pair| pair[1] < pair[0]).count();
+
+    SortedTrovesCrossCheck {
+        listed: listed.len(),
+        active: active.len(),
+        missing_from_list,
+        missing_from_db,
+        rate_inversions,
+    }
+}
+
+/// Mirror of the on-chain `SortedTroves` list, seeded by walking it and kept current from events
+#[derive(Clone)]
+pub struct SortedTrovesMirror {
+    sorted_troves: Address,
+    provider: Arc<StrategyProvider>,
+    list: Arc<RwLock<SortedList>>,
+    seeded: Arc<RwLock<bool>>,
+}
+
+impl SortedTrovesMirror {
+    pub fn new(sorted_troves: Address, provider: Arc<StrategyProvider>) -> Self {
+        Self {
+            sorted_troves,
+            provider,
+            list: Arc::new(RwLock::new(SortedList::default())),
+            seeded: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// Rebuild the mirror by walking the list with `getFirst`/`getNext`
+    pub async fn seed(&self) -> Result<usize> {
+        let sorted_troves = SortedTroves::new(self.sorted_troves, &*self.provider);
+        let start_time = std::time::Instant::now();
+
+        let mut list = SortedList::default();
+        let mut prev = U256::ZERO;
+        let mut cursor = sorted_troves.getFirst().call().await?;
+        while !cursor.is_zero() {
+            list.insert(cursor, prev, U256::ZERO);
+            prev = cursor;
+            cursor = sorted_troves.getNext(cursor).call().await?;
+        }
+
+        let size = list.nodes.len();
+        *self.list.write().await = list;
+        *self.seeded.write().await = true;
+        info!("📜 Seeded SortedTroves mirror with {} troves in {:?}", size, start_time.elapsed());
+        Ok(size)
+    }
+
+    /// Re-link a trove after an event touched it, reseeding if its neighbours are unknown
+    pub async fn sync_trove(&self, trove_id: U256) -> Result<()> {
+        if !*self.seeded.read().await {
+            return Ok(());
+        }
+
+        let sorted_troves = SortedTroves::new(self.sorted_troves, &*self.provider);
+        if !sorted_troves.contains(trove_id).call().await? {
+            self.list.write().await.remove(trove_id);
+            return Ok(());
+        }
+
+        let prev = sorted_troves.getPrev(trove_id).call().await?;
+        let next = sorted_troves.getNext(trove_id).call().await?;
+
+        let linked = {
+            let mut list = self.list.write().await;
+            list.remove(trove_id);
+            list.insert(trove_id, prev, next)
+        };
+        if !linked {
+            warn!("📜 SortedTroves mirror out of sync around trove {} - reseeding", trove_id);
+            self.seed().await?;
+        }
+        Ok(())
+    }
+
+    /// Candidate ids in protocol order, starting from the last node
+    pub async fn candidates(&self, limit: usize) -> Vec<U256> {
+        self.list.read().await.ids_from_last(limit)
+    }
+
+    pub async fn size(&self) -> usize {
+        self.list.read().await.nodes.len()
+    }
+
+    /// Compare the mirrored list against the DB's open troves
+    pub async fn cross_check(&self, open_troves: &[Trove]) -> SortedTrovesCrossCheck {
+        let list = self.list.read().await;
+        cross_check(&list.ids_from_last(list.nodes.len()), open_troves)
+    }
+}
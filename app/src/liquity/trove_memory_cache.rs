@@ -1,29 +1,116 @@
-use std::sync::{Arc, RwLock};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use alloy::primitives::Uint;
+use dashmap::DashMap;
 use eyre::Result;
 use log::info;
+use tokio::sync::{Mutex, RwLock};
 
 
-use crate::db::{DatabaseStore,  store::Trove};
+use crate::{
+    db::{DatabaseStore, store::{Trove, TroveStatus}, u256::StoredU256},
+    metrics,
+};
 
 
-#[derive(Clone, Debug)]
-pub struct CachedTrovesData {
-    pub troves: Vec<Trove>, // Replace with your actual Trove struct
-    pub cached_at: u64,
+/// Ordered views over the open troves in the book, keyed by a sort value and the trove id.
+/// The backtester keeps one too, so replays walk candidates in the live order.
+#[derive(Debug, Default)]
+pub(crate) struct TroveIndex {
+    by_liquidation_price: BTreeSet<(StoredU256, String)>,
+    by_icr: BTreeSet<(StoredU256, String)>,
+    by_liquidation_price_until: BTreeSet<(i64, String)>,
 }
 
+impl TroveIndex {
+    pub(crate) fn insert(&mut self, trove: &Trove) {
+        let id = trove.trove_id.clone();
+        self.by_liquidation_price.insert((trove.liquidation_price, id.clone()));
+        self.by_icr.insert((trove.icr_key, id.clone()));
+        self.by_liquidation_price_until.insert((trove.liquidation_price_until, id));
+    }
+
+    pub(crate) fn remove(&mut self, trove: &Trove) {
+        let id = trove.trove_id.clone();
+        self.by_liquidation_price.remove(&(trove.liquidation_price, id.clone()));
+        self.by_icr.remove(&(trove.icr_key, id.clone()));
+        self.by_liquidation_price_until.remove(&(trove.liquidation_price_until, id));
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Ids of the `limit` troves with the lowest stored ICR, lowest first
+    pub(crate) fn lowest_icr(&self, limit: usize) -> Vec<String> {
+        self.by_icr.iter().take(limit).map(|(_, id)| id.clone()).collect()
+    }
+
+    /// Ids of the troves whose liquidation price is above `price`, most underwater first
+    pub(crate) fn above_liquidation_price(&self, price: Uint<256, 4>) -> Vec<String> {
+        self.by_liquidation_price
+            .range((StoredU256(price), String::new())..)
+            .rev()
+            .filter(|(key, _)| key.0 > price)
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+
+    /// Ids of the troves whose liquidation price is valid until `timestamp` or earlier
+    pub(crate) fn expired_liquidation_prices(&self, timestamp: i64) -> Vec<String> {
+        self.by_liquidation_price_until
+            .range(..(timestamp + 1, String::new()))
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+}
+
+/// Hit/miss and reload counters, mirrored into the Prometheus metrics
+#[derive(Debug, Default)]
+struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    loaded_at: AtomicU64,
+    last_reload_ms: AtomicU64,
+}
+
+impl CacheStats {
+    fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        metrics::TROVE_CACHE_HITS.inc();
+    }
+
+    fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        metrics::TROVE_CACHE_MISSES.inc();
+    }
+}
+
+/// In-memory book of open troves, loaded once from the DB and then kept current per event.
+/// Every mutation is written through to SQLite first so the DB stays the durable copy.
+///
+/// The ordered index sits behind a Tokio lock and the book itself in a `DashMap`; neither
+/// poisons on panic. Writers always take the index lock before touching the map.
 #[derive(Clone)]
 pub struct TroveMemoryCache {
-    cached_data: Arc<RwLock<Option<CachedTrovesData>>>,
-    cache_ttl_seconds: u64,
+    troves: Arc<DashMap<String, Trove>>,
+    index: Arc<RwLock<TroveIndex>>,
+    loaded: Arc<AtomicBool>,
+    /// Serializes loads so concurrent first reads don't each reload the book
+    load_lock: Arc<Mutex<()>>,
+    stats: Arc<CacheStats>,
 }
 
 impl TroveMemoryCache {
-    pub fn new(cache_ttl_seconds: u64) -> Self {
+    pub fn new() -> Self {
         Self {
-            cached_data: Arc::new(RwLock::new(None)),
-            cache_ttl_seconds,
+            troves: Arc::new(DashMap::new()),
+            index: Arc::new(RwLock::new(TroveIndex::default())),
+            loaded: Arc::new(AtomicBool::new(false)),
+            load_lock: Arc::new(Mutex::new(())),
+            stats: Arc::new(CacheStats::default()),
         }
     }
 
@@ -34,88 +121,211 @@ impl TroveMemoryCache {
             .as_secs()
     }
 
-    /// Get sorted troves - from memory if available and valid, otherwise from DB
-    pub async fn get_sorted_troves(&self, store: &Arc<DatabaseStore>) -> Result<Vec<Trove>> {
-        let now = Self::get_current_timestamp();
-        
-        // Check if we have valid cached data
+    /// (Re)load every open trove from the DB
+    pub async fn load(&self, store: &Arc<dyn DatabaseStore>) -> Result<usize> {
+        let _load = self.load_lock.lock().await;
+        let start_time = Instant::now();
+        let troves_from_db = store.get_open_troves().await?;
+
         {
-            let cached = self.cached_data.read().unwrap();
-            if let Some(ref data) = *cached {
-                // Check if cache is still valid
-                if now.saturating_sub(data.cached_at) <= self.cache_ttl_seconds {
-                    info!("Cache hit - returning {} troves from memory", data.troves.len());
-                    return Ok(data.troves.clone());
-                }
-                info!("Cache expired - fetching fresh data from DB");
-            } else {
-                info!("Cache empty - fetching initial data from DB");
+            let mut index = self.index.write().await;
+            index.clear();
+            self.troves.clear();
+            for trove in troves_from_db {
+                index.insert(&trove);
+                self.troves.insert(trove.trove_id.clone(), trove);
             }
         }
 
-        // Cache miss or expired - fetch from database
-        let troves_from_db = store.get_all_active_troves().await?;
-        
-        if troves_from_db.is_empty() {
-            info!("No active troves found in DB");
-            return Ok(Vec::new());
+        let elapsed = start_time.elapsed();
+        self.stats.loaded_at.store(Self::get_current_timestamp(), Ordering::Relaxed);
+        self.stats.last_reload_ms.store(elapsed.as_millis() as u64, Ordering::Relaxed);
+        metrics::TROVE_CACHE_RELOAD_SECONDS.observe(elapsed.as_secs_f64());
+        metrics::TROVE_CACHE_TROVES.set(self.troves.len() as i64);
+
+        self.loaded.store(true, Ordering::Release);
+        info!("Loaded {} open troves into memory in {:?}", self.troves.len(), elapsed);
+        Ok(self.troves.len())
+    }
+
+    /// Load the book on first use
+    pub async fn ensure_loaded(&self, store: &Arc<dyn DatabaseStore>) -> Result<()> {
+        if self.loaded.load(Ordering::Acquire) {
+            self.stats.hit();
+            return Ok(());
         }
 
-        info!("Fetched {} troves from DB, caching in memory", troves_from_db.len());
+        self.stats.miss();
+        self.load(store).await?;
+        Ok(())
+    }
 
-        // Store in cache
-        {
-            let mut cached = self.cached_data.write().unwrap();
-            *cached = Some(CachedTrovesData {
-                troves: troves_from_db.clone(),
-                cached_at: now,
-            });
+    /// Replace the in-memory copy of a trove, dropping it from the book once it is closed
+    async fn apply(&self, trove: Trove) {
+        let open = TroveStatus::parse(&trove.status).is_some_and(|status| status.is_open());
+        let mut index = self.index.write().await;
+        if let Some((_, previous)) = self.troves.remove(&trove.trove_id) {
+            index.remove(&previous);
+        }
+        if open {
+            index.insert(&trove);
+            self.troves.insert(trove.trove_id.clone(), trove);
+        }
+        metrics::TROVE_CACHE_TROVES.set(self.troves.len() as i64);
+    }
+
+    /// Write a trove through to the DB and the book
+    pub async fn upsert(&self, store: &Arc<dyn DatabaseStore>, trove: &Trove) -> Result<()> {
+        store.upsert_trove(trove).await?;
+        self.apply(trove.clone()).await;
+        Ok(())
+    }
+
+    pub async fn set_status(
+        &self,
+        store: &Arc<dyn DatabaseStore>,
+        trove_id: &str,
+        status: TroveStatus,
+        block_number: i64,
+    ) -> Result<()> {
+        store.set_trove_status(trove_id, status, block_number).await?;
+
+        let existing = match self.get(trove_id) {
+            Some(trove) => Some(trove),
+            // A trove coming back from a closed status is not in the book yet
+            None if status.is_open() => {
+                self.stats.miss();
+                store.get_trove_by_id(trove_id).await?
+            }
+            None => None,
+        };
+        if let Some(mut trove) = existing {
+            trove.status = status.as_str().to_string();
+            trove.last_updated = block_number;
+            self.apply(trove).await;
         }
+        Ok(())
+    }
+
+    pub async fn set_liquidation_price(
+        &self,
+        store: &Arc<dyn DatabaseStore>,
+        trove_id: &str,
+        last_updated_at: i64,
+        liquidation_price: Uint<256, 4>,
+        liquidation_price_until: i64,
+    ) -> Result<()> {
+        store
+            .set_liquidation_price(trove_id, last_updated_at, liquidation_price, liquidation_price_until)
+            .await?;
+
+        if let Some(mut trove) = self.get(trove_id) {
+            trove.last_updated_at = last_updated_at;
+            trove.liquidation_price = liquidation_price.into();
+            trove.liquidation_price_until = liquidation_price_until;
+            self.apply(trove).await;
+        }
+        Ok(())
+    }
+
+    /// Mark troves as liquidated at `block_number` in the DB and drop them from the book
+    pub async fn close(
+        &self,
+        store: &Arc<dyn DatabaseStore>,
+        trove_ids: &[Uint<256, 4>],
+        block_number: i64,
+    ) -> Result<()> {
+        store.close_troves(trove_ids, block_number).await?;
+
+        let mut index = self.index.write().await;
+        for trove_id in trove_ids {
+            if let Some((_, trove)) = self.troves.remove(&trove_id.to_string()) {
+                index.remove(&trove);
+            }
+        }
+        metrics::TROVE_CACHE_TROVES.set(self.troves.len() as i64);
+        Ok(())
+    }
 
-        Ok(troves_from_db)
+    pub fn get(&self, trove_id: &str) -> Option<Trove> {
+        self.troves.get(trove_id).map(|trove| trove.clone())
     }
 
-    /// Clear the memory cache
-    pub fn clear_memory(&self) {
-        let mut cached = self.cached_data.write().unwrap();
-        *cached = None;
-        info!("Memory cache cleared");
+    /// Open troves among `trove_ids`, in the given order
+    pub fn get_many(&self, trove_ids: &[String]) -> Vec<Trove> {
+        trove_ids.iter().filter_map(|trove_id| self.get(trove_id)).collect()
     }
 
-    /// Get cache info for debugging
-    pub fn _get_cache_info(&self) -> _CacheInfo {
-        let cached = self.cached_data.read().unwrap();
+    pub fn trove_count(&self) -> usize {
+        self.troves.len()
+    }
+
+    pub fn all(&self) -> Vec<Trove> {
+        self.troves.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    /// Get the `limit` open troves with the lowest stored ICR, lowest first
+    pub async fn get_sorted_troves(&self, store: &Arc<dyn DatabaseStore>, limit: usize) -> Result<Vec<Trove>> {
+        self.ensure_loaded(store).await?;
+        let ids = self.index.read().await.lowest_icr(limit);
+        Ok(self.get_many(&ids))
+    }
+
+    /// Open troves whose liquidation price is above `price`, most underwater first
+    pub async fn above_liquidation_price(
+        &self,
+        store: &Arc<dyn DatabaseStore>,
+        price: Uint<256, 4>,
+    ) -> Result<Vec<Trove>> {
+        self.ensure_loaded(store).await?;
+        let ids = self.index.read().await.above_liquidation_price(price);
+        Ok(self.get_many(&ids))
+    }
+
+    /// Open troves whose liquidation price only accounts for interest up to `timestamp` or earlier
+    pub async fn expired_liquidation_prices(
+        &self,
+        store: &Arc<dyn DatabaseStore>,
+        timestamp: i64,
+    ) -> Result<Vec<Trove>> {
+        self.ensure_loaded(store).await?;
+        let ids = self.index.read().await.expired_liquidation_prices(timestamp);
+        Ok(self.get_many(&ids))
+    }
+
+    /// Live cache statistics
+    pub async fn cache_info(&self) -> CacheInfo {
         let now = Self::get_current_timestamp();
-        
-        match *cached {
-            Some(ref data) => {
-                let age_seconds = now.saturating_sub(data.cached_at);
-                let is_valid = age_seconds <= self.cache_ttl_seconds;
-                
-                _CacheInfo {
-                    is_cached: true,
-                    trove_count: data.troves.len(),
-                    age_seconds,
-                    is_valid,
-                    ttl_seconds: self.cache_ttl_seconds,
-                }
-            },
-            None => _CacheInfo {
-                is_cached: false,
-                trove_count: 0,
-                age_seconds: 0,
-                is_valid: false,
-                ttl_seconds: self.cache_ttl_seconds,
-            }
+        let is_loaded = self.loaded.load(Ordering::Acquire);
+        let loaded_at = self.stats.loaded_at.load(Ordering::Relaxed);
+
+        CacheInfo {
+            is_loaded,
+            trove_count: self.troves.len(),
+            indexed_by_price: self.index.read().await.by_liquidation_price.len(),
+            hits: self.stats.hits.load(Ordering::Relaxed),
+            misses: self.stats.misses.load(Ordering::Relaxed),
+            last_reload_ms: self.stats.last_reload_ms.load(Ordering::Relaxed),
+            age_seconds: if is_loaded { now.saturating_sub(loaded_at) } else { 0 },
         }
     }
 }
 
-#[derive(Debug)]
-pub struct _CacheInfo {
-    pub is_cached: bool,
+impl Default for TroveMemoryCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheInfo {
+    pub is_loaded: bool,
     pub trove_count: usize,
+    pub indexed_by_price: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// How long the last load from the DB took
+    pub last_reload_ms: u64,
+    /// Seconds since the book was loaded from the DB
     pub age_seconds: u64,
-    pub is_valid: bool,
-    pub ttl_seconds: u64,
 }
@@ -1,131 +1,244 @@
-mod collector;
-mod config;
-mod db;
-mod liquity;
-mod multicall;
-mod strategy;
+mod cli;
 
-use collector::{BlockCollector, LogCollector};
-use config::get_info;
-use db::{DatabaseStore, initialize_database};
+use app::{
+    AddressRegistry, DefaultProvider,
+    collector::{BlockCollector, LogCollector},
+    config::{CandidateSource, ProtocolConfig, RedemptionConfig, get_info},
+    db::{DatabaseStore, initialize_database},
+    failover::FailoverTransport,
+    health::{self, HealthChecker, HealthThresholds},
+    liquity::{
+        branch_monitor::BranchMonitor, liquidation_recorder::LiquidationRecorder,
+        liquity_exexcution::LiquityExecutor,
+        liquity_strategy::{LiquityStrategy, StrategyProvider},
+        reconciler::TroveReconciler, redemption_strategy::RedemptionStrategy,
+        sorted_troves::SortedTrovesMirror,
+    },
+    metrics::{self, RpcMetricsLayer},
+    notifier::Notifier,
+    replay::RecordingLayer,
+};
+use clap::Parser;
+use cli::{Cli, Command};
 
 use alloy::{
     network::EthereumWallet,
-    providers::{
-        Identity, ProviderBuilder, RootProvider,
-        fillers::{
-            BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller,
-            WalletFiller,
-        },
-    },
+    primitives::{Address, U256},
+    providers::ProviderBuilder,
+    rpc::client::ClientBuilder,
     signers::local::PrivateKeySigner,
-    sol,
 };
 use eyre::Result;
-use serde::{Deserialize, Serialize};
-use std::{sync::Arc};
-
-
-use crate::{
-    liquity::{liquity_exexcution::LiquityExecutor, liquity_strategy::LiquityStrategy}
-};
+use std::{sync::Arc, time::Duration};
 
-pub type DefaultProvider = FillProvider<
-    JoinFill<
-        JoinFill<
-            Identity,
-            JoinFill<GasFiller, JoinFill<BlobGasFiller, JoinFill<NonceFiller, ChainIdFiller>>>,
-        >,
-        WalletFiller<EthereumWallet>,
-    >,
-    RootProvider,
->;
-
-sol!(
-    #[derive(Debug, Default, Serialize, Deserialize)]
-    #[sol(rpc)]
-    AddressRegistry,
-    "../artifacts/AddressRegistry.sol/AddressesRegistry.json"
-);
-
-sol!(
-    #[derive(Debug, Default, Serialize, Deserialize)]
-    #[sol(rpc)]
-    PriceFeed,
-    "../artifacts/IPriceFeed.sol/IPriceFeed.json"
-);
 //add private key
 const PRIVATE_KEY: &str = "0x";
 
+/// Config, storage, providers and branch contracts shared by every subcommand
+pub struct App {
+    pub config: ProtocolConfig,
+    pub store: Arc<dyn DatabaseStore>,
+    pub provider: Arc<StrategyProvider>,
+    pub transport: FailoverTransport,
+    pub notifier: Notifier,
+    pub signer_address: Address,
+    pub trove_manager: Address,
+    pub sorted_troves: Address,
+    pub price_feed: Address,
+    pub mcr: U256,
+    pub scr: U256,
+    pub executor: LiquityExecutor,
+}
+
+impl App {
+    /// `shadow` forces shadow mode on regardless of the config
+    pub async fn connect(config: ProtocolConfig, shadow: bool) -> Result<Self> {
+        let notifier = Notifier::from_env(
+            Duration::from_secs(config.alert_dedup_secs),
+            config.alert_max_per_minute,
+        );
+
+        // Initialize the database; the URL scheme picks SQLite or PostgreSQL
+        let store = initialize_database(config.database_url).await?;
+
+        //intiailize the instances
+        let signer: PrivateKeySigner = PRIVATE_KEY.parse().expect("should parse private key");
+        let signer_address = signer.address();
+        let wallet = EthereumWallet::from(signer);
+
+        // Every client goes through the RPC metrics layer and shares one failover transport;
+        // with `RPC_RECORD_FILE` set, traffic is also captured as a replayable fixture
+        let transport =
+            FailoverTransport::new(config.rpc_url, config.fallback_rpc_urls, notifier.clone())?;
+        let recorder = RecordingLayer::from_env()?;
+        let client = ClientBuilder::default()
+            .layer(RpcMetricsLayer)
+            .layer(recorder.clone())
+            .transport(transport.clone(), false);
+        let provider = Arc::new(ProviderBuilder::new().connect_client(client));
+        let http_client = ClientBuilder::default()
+            .layer(RpcMetricsLayer)
+            .layer(recorder)
+            .transport(transport.clone(), false);
+        let http_provider: Arc<DefaultProvider> =
+            Arc::new(ProviderBuilder::new().wallet(wallet).connect_client(http_client));
+
+        let address_registry_instance = AddressRegistry::new(config.address_registry, &*provider);
+        let mcr = address_registry_instance.MCR().call().await?;
+        let scr = address_registry_instance.SCR().call().await?;
+        let trove_manager = address_registry_instance.troveManager().call().await?;
+        let price_feed = address_registry_instance.priceFeed().call().await?;
+        let sorted_troves = address_registry_instance.sortedTroves().call().await?;
+
+        let mut executor = LiquityExecutor::new(
+            config.liquidator_address,
+            trove_manager,
+            http_provider.clone(),
+            provider.clone(),
+        );
+        executor.set_notifier(notifier.clone());
+        if shadow || config.shadow_mode {
+            log::warn!("🕶️ Shadow mode - transactions are simulated and recorded, never sent");
+            executor.set_shadow_mode(store.clone());
+        }
+
+        Ok(Self {
+            config,
+            store,
+            provider,
+            transport,
+            notifier,
+            signer_address,
+            trove_manager,
+            sorted_troves,
+            price_feed,
+            mcr,
+            scr,
+            executor,
+        })
+    }
+
+    /// A liquidation strategy over the branch, without a SortedTroves mirror
+    pub async fn strategy(&self) -> LiquityStrategy {
+        let liquidation_recorder = LiquidationRecorder::new(
+            self.trove_manager,
+            self.config.liquidator_address,
+            self.config.native_price_oracle,
+            self.store.clone(),
+            self.provider.clone(),
+        );
+
+        let mut liquity_strategy = LiquityStrategy::new(
+            self.trove_manager,
+            self.store.clone(),
+            self.provider.clone(),
+            self.config.oracle_address,
+            self.mcr,
+            self.executor.clone(),
+            liquidation_recorder,
+        )
+        .await;
+        liquity_strategy.set_liquidation_price_horizon(self.config.liquidation_price_horizon_secs);
+        liquity_strategy
+    }
+
+    /// A log collector feeding TroveManager logs from `start_block` into `strategy`
+    pub async fn log_collector(
+        &self,
+        strategy: &LiquityStrategy,
+        start_block: u64,
+    ) -> LogCollector {
+        let mut log_collector = LogCollector::new();
+        log_collector.set_contract_address(self.trove_manager);
+        log_collector.set_start_block(start_block);
+        log_collector.connect_provider(self.provider.clone()).await;
+        log_collector._add_strategy(Box::new(strategy.clone())).await;
+        log_collector
+    }
+
+    /// Last ingested block, starting from the configured block on a fresh database
+    pub async fn last_block(&self) -> Result<u64> {
+        let mut last_block = self.store.get_last_block().await?;
+        log::info!("📦 Last block from db: {}", last_block);
+        if last_block == 0 {
+            self.store.set_last_block(self.config.start_block as i64).await?;
+            last_block = self.config.start_block as i64;
+        }
+        Ok(last_block as u64)
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
+    dotenv::dotenv().ok();
     env_logger::init();
 
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: cargo run -- <protocol-name>");
-        std::process::exit(1);
-    }
-
-    let protocol = &args[1];
-    let config = get_info(protocol).unwrap_or_else(|| {
-        eprintln!("Unknown protocol: {}", protocol);
+    let cli = Cli::parse();
+    let config = get_info(&cli.protocol).unwrap_or_else(|| {
+        eprintln!("Unknown protocol: {}", cli.protocol);
         std::process::exit(1);
     });
 
-    // Initialize the database
-
-    let pool = initialize_database(config.database_url).await?;
-
-    // Create the store interface
-    let store = DatabaseStore::new(pool);
-    let store = Arc::new(store);
-
-    let mut last_block = store.get_last_block().await?;
-    println!("last block from db: {}", last_block);
-    if last_block == 0 {
-        store.set_last_block(config.start_block as i64).await?;
-        last_block = config.start_block as i64;
+    // Backtests replay a local file and must not touch the RPC or database
+    if let Some(Command::Backtest(args)) = &cli.command {
+        return cli::backtest(&config, args);
     }
 
-    //intiailize the instances
-    let signer: PrivateKeySigner = PRIVATE_KEY.parse().expect("should parse private key");
-    let wallet = EthereumWallet::from(signer);
+    metrics::register(&cli.protocol)?;
+    let app = App::connect(config, cli.shadow).await?;
 
-    let provider = ProviderBuilder::new().connect(config.rpc_url).await?;
-    let http_provider = ProviderBuilder::new().wallet(wallet).connect_http(config.rpc_url.parse()?);
-
-    let provider = Arc::new(provider);
-    let http_provider: Arc<DefaultProvider> = Arc::new(http_provider);
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => run(app).await,
+        Command::Backfill { from, to } => cli::backfill(&app, from, to).await,
+        Command::Resync => cli::resync(&app).await,
+        Command::Troves { lowest } => cli::troves(&app, lowest).await,
+        Command::Trove { id } => cli::trove(&app, &id).await,
+        Command::Liquidate { ids, dry_run } => cli::liquidate(&app, &ids, dry_run).await,
+        Command::ShadowReport { limit } => cli::shadow_report(&app, limit).await,
+        Command::Backtest(_) => unreachable!("backtests run before connecting"),
+    }
+}
 
-    let address_registry_instance = AddressRegistry::new(config.address_registry, &*provider);
-    let mcr = address_registry_instance.MCR().call().await?;
-    let trove_manager = address_registry_instance.troveManager().call().await?;
+/// Backfill history, then follow new blocks with every strategy until the process exits
+async fn run(app: App) -> Result<()> {
+    let config = &app.config;
+    let last_block = app.last_block().await?;
 
-    let liquity_executor = LiquityExecutor::new(
-        config.liquidator_address,
-        trove_manager,
-        http_provider.clone(),
-        provider.clone(),
+    let health_checker = HealthChecker::new(
+        app.store.clone(),
+        app.provider.clone(),
+        app.signer_address,
+        HealthThresholds {
+            max_block_lag: config.max_block_lag,
+            max_oracle_staleness_secs: config.max_oracle_staleness_secs,
+            min_signer_balance: U256::from(config.min_signer_balance_wei),
+            max_stall_secs: config.max_stall_secs,
+        },
     );
+    let watchdog = health_checker.clone();
+    let watchdog_notifier = app.notifier.clone();
+    tokio::spawn(async move { watchdog.watch(watchdog_notifier, Duration::from_secs(60)).await });
 
-    let liquity_strategy = LiquityStrategy::new(
-        trove_manager,
-        store.clone(),
-        provider.clone(),
-        config.oracle_address,
-        mcr,
-        liquity_executor.clone(),
-    )
-    .await;
+    let metrics_addr = config.metrics_addr;
+    tokio::spawn(async move {
+        if let Err(e) = metrics::serve(metrics_addr, health::router(health_checker)).await {
+            log::error!("❌ Metrics endpoint failed: {}", e);
+        }
+    });
 
-    let mut log_collector = LogCollector::new();
-    log_collector.set_contract_address(trove_manager);
-    log_collector.set_start_block(last_block as u64);
-    log_collector.connect_provider(provider.clone()).await;
-    log_collector._add_strategy(Box::new(liquity_strategy.clone())).await;
+    let mut liquity_strategy = app.strategy().await;
+    if config.candidate_source == CandidateSource::SortedTroves {
+        let sorted_troves_mirror =
+            SortedTrovesMirror::new(app.sorted_troves, app.provider.clone());
+        sorted_troves_mirror.seed().await?;
+        liquity_strategy.set_sorted_troves(sorted_troves_mirror);
+    } else {
+        liquity_strategy.set_candidate_source(config.candidate_source);
+    }
+    liquity_strategy.backfill_update_timestamps().await?;
 
+    let mut log_collector = app.log_collector(&liquity_strategy, last_block).await;
     loop {
         let new_block = log_collector.start_listening_with_history().await?;
         let current_block = log_collector.get_current_block_number().await?;
@@ -133,13 +246,46 @@ async fn main() -> Result<()> {
             break;
         }
     }
+    health::set_backfill_complete();
 
-    let ws_provider = ProviderBuilder::new().connect_http(config.rpc_url.parse().unwrap());
+    let ws_client =
+        ClientBuilder::default().layer(RpcMetricsLayer).transport(app.transport.clone(), false);
+    let ws_provider = ProviderBuilder::new().connect_client(ws_client);
     let ws_provider = Arc::new(ws_provider);
 
+    let mut reconciler = TroveReconciler::new(
+        app.trove_manager,
+        app.store.clone(),
+        app.provider.clone(),
+        config.reconcile_interval_secs,
+        config.reconcile_batch_size,
+    );
+    reconciler.set_memory_cache(liquity_strategy.memory_cache());
+    tokio::spawn(async move { reconciler.run().await });
+
     let mut block_collector = BlockCollector::new();
+    block_collector.set_notifier(app.notifier.clone(), config.strategy_error_streak);
     block_collector.connect_provider(ws_provider.clone()).await;
     block_collector.add_strategy(Box::new(liquity_strategy.clone())).await;
+
+    let branch_monitor =
+        BranchMonitor::new(app.trove_manager, config.oracle_address, app.scr, app.provider.clone());
+    let branch_health = branch_monitor.health();
+    block_collector.add_strategy(Box::new(branch_monitor)).await;
+
+    // Registered on every branch: it is the only strategy acting on urgent-redemption mode
+    let urgent_only = RedemptionConfig::urgent_only();
+    let mut redemption_strategy = RedemptionStrategy::new(
+        app.store.clone(),
+        app.provider.clone(),
+        app.executor.clone(),
+        app.trove_manager,
+        app.price_feed,
+        config.oracle_address,
+        config.redemption.as_ref().unwrap_or(&urgent_only),
+    );
+    redemption_strategy.set_branch_health(branch_health);
+    block_collector.add_strategy(Box::new(redemption_strategy)).await;
     block_collector.start_listening().await?;
 
     // let _ = tokio::spawn(async move{
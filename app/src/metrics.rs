@@ -0,0 +1,372 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, OnceLock},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use alloy::{
+    rpc::json_rpc::{Id, RequestPacket, ResponsePacket},
+    transports::{TransportError, TransportFut},
+};
+use axum::{Router, http::header, response::IntoResponse, routing::get};
+use eyre::Result;
+use log::{error, info};
+use prometheus::{
+    Counter, Encoder, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tower::{Layer, Service};
+
+use crate::liquity::{reconciler::DriftSummary, sorted_troves::SortedTrovesCrossCheck};
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+// ========== Blocks ==========
+
+pub static HEAD_BLOCK: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("head_block", "Latest block reported by the RPC node").expect("valid metric")
+});
+
+pub static PROCESSED_BLOCK: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("processed_block", "Last block whose logs have been processed")
+        .expect("valid metric")
+});
+
+pub static BLOCK_LAG: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("block_lag", "Blocks between the head and the last processed block")
+        .expect("valid metric")
+});
+
+pub static LOGS_PROCESSED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new("logs_processed_total", "TroveManager logs processed, by event"),
+        &["event"],
+    )
+    .expect("valid metric")
+});
+
+pub static STRATEGY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    HistogramVec::new(
+        HistogramOpts::new("strategy_execution_seconds", "Time taken by one strategy execution"),
+        &["strategy"],
+    )
+    .expect("valid metric")
+});
+
+pub static STRATEGY_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new("strategy_errors_total", "Strategy executions that returned an error"),
+        &["strategy"],
+    )
+    .expect("valid metric")
+});
+
+// ========== Troves ==========
+
+pub static ACTIVE_TROVES: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("active_troves", "Open (active or zombie) troves tracked").expect("valid metric")
+});
+
+pub static LOWEST_ICR: LazyLock<Gauge> = LazyLock::new(|| {
+    Gauge::new("lowest_icr", "Lowest ICR among the troves checked in the last block")
+        .expect("valid metric")
+});
+
+pub static LIQUIDATION_CANDIDATES: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("liquidation_candidates_total", "Troves found below MCR")
+        .expect("valid metric")
+});
+
+// ========== Liquidation Transactions ==========
+
+pub static LIQUIDATION_TXS_SENT: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("liquidation_txs_sent_total", "Transactions sent through the executor")
+        .expect("valid metric")
+});
+
+pub static LIQUIDATION_TXS_MINED: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("liquidation_txs_mined_total", "Executor transactions mined successfully")
+        .expect("valid metric")
+});
+
+pub static LIQUIDATION_TXS_REVERTED: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("liquidation_txs_reverted_total", "Executor transactions mined but reverted")
+        .expect("valid metric")
+});
+
+pub static GAS_SPENT: LazyLock<Counter> = LazyLock::new(|| {
+    Counter::new("gas_spent_wei_total", "Gas cost (wei) of executor transactions")
+        .expect("valid metric")
+});
+
+// ========== Oracle ==========
+
+pub static ORACLE_PRICE: LazyLock<Gauge> = LazyLock::new(|| {
+    Gauge::new("oracle_price", "Last collateral price read from the oracle (USD)")
+        .expect("valid metric")
+});
+
+pub static ORACLE_LAST_READ: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("oracle_last_read_timestamp", "Unix time of the last successful oracle read")
+        .expect("valid metric")
+});
+
+pub static ORACLE_AGE: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("oracle_age_seconds", "Age of the oracle answer when last read")
+        .expect("valid metric")
+});
+
+// ========== RPC ==========
+
+pub static RPC_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(Opts::new("rpc_requests_total", "RPC requests, by method"), &["method"])
+        .expect("valid metric")
+});
+
+pub static RPC_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new("rpc_errors_total", "RPC requests that failed, by method"),
+        &["method"],
+    )
+    .expect("valid metric")
+});
+
+pub static RPC_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    HistogramVec::new(
+        HistogramOpts::new("rpc_request_seconds", "RPC round-trip time, by method"),
+        &["method"],
+    )
+    .expect("valid metric")
+});
+
+// ========== Trove Cache ==========
+
+pub static TROVE_CACHE_HITS: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("trove_cache_hits_total", "Trove book reads served from memory")
+        .expect("valid metric")
+});
+
+pub static TROVE_CACHE_MISSES: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("trove_cache_misses_total", "Trove book reads that had to go to the database")
+        .expect("valid metric")
+});
+
+pub static TROVE_CACHE_RELOAD_SECONDS: LazyLock<Histogram> = LazyLock::new(|| {
+    Histogram::with_opts(HistogramOpts::new(
+        "trove_cache_reload_seconds",
+        "Time taken to load the trove book from the database",
+    ))
+    .expect("valid metric")
+});
+
+pub static TROVE_CACHE_TROVES: LazyLock<IntGauge> = LazyLock::new(|| {
+    IntGauge::new("trove_cache_troves", "Open troves held in the in-memory trove book")
+        .expect("valid metric")
+});
+
+// ========== Reconciler ==========
+
+pub static RECONCILE_CHECKED: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("reconcile_checked_total", "Stored troves compared against chain state")
+        .expect("valid metric")
+});
+
+pub static RECONCILE_DRIFT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    IntCounterVec::new(
+        Opts::new("reconcile_drift_total", "Stored troves that disagreed with chain, by field"),
+        &["field"],
+    )
+    .expect("valid metric")
+});
+
+pub static RECONCILE_REPAIRED: LazyLock<IntCounter> = LazyLock::new(|| {
+    IntCounter::new("reconcile_repaired_total", "Drifted troves rewritten from chain state")
+        .expect("valid metric")
+});
+
+// ========== SortedTroves mirror ==========
+
+pub static SORTED_TROVES_DRIFT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "sorted_troves_drift",
+            "Disagreements between the SortedTroves mirror and the DB at the last cross-check",
+        ),
+        &["kind"],
+    )
+    .expect("valid metric")
+});
+
+/// Register every metric under a registry labelled with the protocol being liquidated
+pub fn register(protocol: &str) -> Result<()> {
+    let labels = HashMap::from([("protocol".to_string(), protocol.to_string())]);
+    let registry = Registry::new_custom(Some("liquidator".to_string()), Some(labels))?;
+
+    registry.register(Box::new(HEAD_BLOCK.clone()))?;
+    registry.register(Box::new(PROCESSED_BLOCK.clone()))?;
+    registry.register(Box::new(BLOCK_LAG.clone()))?;
+    registry.register(Box::new(LOGS_PROCESSED.clone()))?;
+    registry.register(Box::new(STRATEGY_DURATION.clone()))?;
+    registry.register(Box::new(STRATEGY_ERRORS.clone()))?;
+    registry.register(Box::new(ACTIVE_TROVES.clone()))?;
+    registry.register(Box::new(LOWEST_ICR.clone()))?;
+    registry.register(Box::new(LIQUIDATION_CANDIDATES.clone()))?;
+    registry.register(Box::new(LIQUIDATION_TXS_SENT.clone()))?;
+    registry.register(Box::new(LIQUIDATION_TXS_MINED.clone()))?;
+    registry.register(Box::new(LIQUIDATION_TXS_REVERTED.clone()))?;
+    registry.register(Box::new(GAS_SPENT.clone()))?;
+    registry.register(Box::new(ORACLE_PRICE.clone()))?;
+    registry.register(Box::new(ORACLE_LAST_READ.clone()))?;
+    registry.register(Box::new(ORACLE_AGE.clone()))?;
+    registry.register(Box::new(RPC_REQUESTS.clone()))?;
+    registry.register(Box::new(RPC_ERRORS.clone()))?;
+    registry.register(Box::new(RPC_DURATION.clone()))?;
+    registry.register(Box::new(TROVE_CACHE_HITS.clone()))?;
+    registry.register(Box::new(TROVE_CACHE_MISSES.clone()))?;
+    registry.register(Box::new(TROVE_CACHE_RELOAD_SECONDS.clone()))?;
+    registry.register(Box::new(TROVE_CACHE_TROVES.clone()))?;
+    registry.register(Box::new(RECONCILE_CHECKED.clone()))?;
+    registry.register(Box::new(RECONCILE_DRIFT.clone()))?;
+    registry.register(Box::new(RECONCILE_REPAIRED.clone()))?;
+    registry.register(Box::new(SORTED_TROVES_DRIFT.clone()))?;
+
+    REGISTRY.set(registry).map_err(|_| eyre::eyre!("Metrics registry already initialized"))
+}
+
+pub fn set_head_block(block_number: u64) {
+    HEAD_BLOCK.set(block_number as i64);
+    BLOCK_LAG.set((HEAD_BLOCK.get() - PROCESSED_BLOCK.get()).max(0));
+}
+
+pub fn set_processed_block(block_number: u64) {
+    PROCESSED_BLOCK.set(block_number as i64);
+    BLOCK_LAG.set((HEAD_BLOCK.get() - PROCESSED_BLOCK.get()).max(0));
+}
+
+pub fn record_reconciliation(summary: &DriftSummary) {
+    RECONCILE_CHECKED.inc_by(summary.checked as u64);
+    RECONCILE_REPAIRED.inc_by(summary.repaired as u64);
+    for (field, count) in [
+        ("collateral", summary.collateral),
+        ("debt", summary.debt),
+        ("interest_rate", summary.interest_rate),
+        ("status", summary.status),
+    ] {
+        RECONCILE_DRIFT.with_label_values(&[field]).inc_by(count as u64);
+    }
+}
+
+pub fn record_sorted_troves_cross_check(check: &SortedTrovesCrossCheck) {
+    for (kind, count) in [
+        ("missing_from_list", check.missing_from_list.len()),
+        ("missing_from_db", check.missing_from_db.len()),
+        ("rate_inversions", check.rate_inversions),
+    ] {
+        SORTED_TROVES_DRIFT.with_label_values(&[kind]).set(count as i64);
+    }
+}
+
+/// Transport layer counting RPC requests, errors and latency per JSON-RPC method
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RpcMetricsLayer;
+
+impl<S> Layer<S> for RpcMetricsLayer {
+    type Service = RpcMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcMetricsService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcMetricsService<S> {
+    inner: S,
+}
+
+impl<S> Service<RequestPacket> for RpcMetricsService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Send
+        + Clone
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let methods: Vec<(Id, String)> = request
+            .requests()
+            .iter()
+            .map(|request| (request.id().clone(), request.method().to_string()))
+            .collect();
+        for (_, method) in &methods {
+            RPC_REQUESTS.with_label_values(&[method]).inc();
+        }
+
+        let start_time = Instant::now();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            let elapsed = start_time.elapsed().as_secs_f64();
+            for (_, method) in &methods {
+                RPC_DURATION.with_label_values(&[method]).observe(elapsed);
+            }
+
+            let method_of = |id: &Id| {
+                methods.iter().find(|(request_id, _)| request_id == id).map(|(_, m)| m.as_str())
+            };
+            match &result {
+                Err(_) => {
+                    for (_, method) in &methods {
+                        RPC_ERRORS.with_label_values(&[method]).inc();
+                    }
+                }
+                Ok(ResponsePacket::Single(response)) if response.is_error() => {
+                    let method = method_of(&response.id).unwrap_or("unknown");
+                    RPC_ERRORS.with_label_values(&[method]).inc();
+                }
+                Ok(ResponsePacket::Batch(responses)) => {
+                    for response in responses.iter().filter(|response| response.is_error()) {
+                        let method = method_of(&response.id).unwrap_or("unknown");
+                        RPC_ERRORS.with_label_values(&[method]).inc();
+                    }
+                }
+                Ok(_) => {}
+            }
+            result
+        })
+    }
+}
+
+/// Render all registered metrics in the Prometheus text format
+fn render() -> String {
+    let Some(registry) = REGISTRY.get() else {
+        return String::new();
+    };
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&registry.gather(), &mut buffer) {
+        error!("❌ Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+async fn metrics_handler() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], render())
+}
+
+/// Serve `/metrics`, plus any `routes` sharing the listener, on `addr` until the process exits
+pub async fn serve(addr: &str, routes: Router) -> Result<()> {
+    let app = Router::new().route("/metrics", get(metrics_handler)).merge(routes);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("📈 Metrics endpoint listening on http://{}/metrics", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use eyre::Result;
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+/// What an alert is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    LiquidationSent,
+    LiquidationMined,
+    LiquidationReverted,
+    BalanceLow,
+    OracleStale,
+    RpcFailover,
+    StrategyErrorStreak,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::LiquidationSent => "liquidation_sent",
+            AlertKind::LiquidationMined => "liquidation_mined",
+            AlertKind::LiquidationReverted => "liquidation_reverted",
+            AlertKind::BalanceLow => "balance_low",
+            AlertKind::OracleStale => "oracle_stale",
+            AlertKind::RpcFailover => "rpc_failover",
+            AlertKind::StrategyErrorStreak => "strategy_error_streak",
+        }
+    }
+
+    fn emoji(&self) -> &'static str {
+        match self {
+            AlertKind::LiquidationSent => "📤",
+            AlertKind::LiquidationMined => "✅",
+            AlertKind::LiquidationReverted => "❌",
+            AlertKind::BalanceLow => "🪫",
+            AlertKind::OracleStale => "⏳",
+            AlertKind::RpcFailover => "🔀",
+            AlertKind::StrategyErrorStreak => "🚨",
+        }
+    }
+}
+
+/// A single notification. Alerts sharing a `key` are deduplicated.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub key: String,
+    pub title: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, key: impl Into<String>, title: impl Into<String>) -> Self {
+        Self { kind, key: key.into(), title: title.into(), fields: Vec::new() }
+    }
+
+    pub fn field(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Plain-text body shared by the chat-style sinks
+    fn text(&self) -> String {
+        let mut text = format!("{} {}", self.kind.emoji(), self.title);
+        for (name, value) in &self.fields {
+            text.push_str(&format!("\n• {}: {}", name, value));
+        }
+        text
+    }
+}
+
+/// A destination alerts are delivered to
+#[async_trait::async_trait]
+pub trait NotifySink: Send + Sync {
+    async fn send(&self, alert: &Alert) -> Result<()>;
+
+    fn name(&self) -> &str;
+}
+
+/// POSTs the alert as JSON to an arbitrary URL
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self { url, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotifySink for WebhookSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let fields: HashMap<&str, &str> =
+            alert.fields.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+        let payload = serde_json::json!({
+            "kind": alert.kind,
+            "key": alert.key,
+            "title": alert.title,
+            "fields": fields,
+        });
+        self.client.post(&self.url).json(&payload).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "webhook"
+    }
+}
+
+/// Sends the alert through a Telegram bot
+pub struct TelegramSink {
+    bot_token: String,
+    chat_id: String,
+    client: reqwest::Client,
+}
+
+impl TelegramSink {
+    pub fn new(bot_token: String, chat_id: String) -> Self {
+        Self { bot_token, chat_id, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotifySink for TelegramSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let payload = serde_json::json!({ "chat_id": self.chat_id, "text": alert.text() });
+        self.client.post(url).json(&payload).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "telegram"
+    }
+}
+
+/// Posts the alert to a Slack incoming webhook
+pub struct SlackSink {
+    webhook_url: String,
+    client: reqwest::Client,
+}
+
+impl SlackSink {
+    pub fn new(webhook_url: String) -> Self {
+        Self { webhook_url, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotifySink for SlackSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let payload = serde_json::json!({ "text": alert.text() });
+        self.client.post(&self.webhook_url).json(&payload).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "slack"
+    }
+}
+
+#[derive(Debug, Default)]
+struct Throttle {
+    /// When each alert key was last delivered
+    last_sent: HashMap<String, Instant>,
+    /// Delivery times within the last minute
+    recent: VecDeque<Instant>,
+}
+
+/// Fans alerts out to every configured sink, dropping repeats of the same key within the dedup
+/// window and anything beyond `max_per_minute`. Delivery happens in the background so callers on
+/// the hot path never wait on a sink.
+#[derive(Clone)]
+pub struct Notifier {
+    sinks: Arc<Vec<Box<dyn NotifySink>>>,
+    throttle: Arc<Mutex<Throttle>>,
+    dedup_window: Duration,
+    max_per_minute: usize,
+}
+
+impl Notifier {
+    pub fn new(
+        sinks: Vec<Box<dyn NotifySink>>,
+        dedup_window: Duration,
+        max_per_minute: usize,
+    ) -> Self {
+        Self {
+            sinks: Arc::new(sinks),
+            throttle: Arc::new(Mutex::new(Throttle::default())),
+            dedup_window,
+            max_per_minute,
+        }
+    }
+
+    /// A notifier with no sinks; every alert is dropped
+    pub fn disabled() -> Self {
+        Self::new(Vec::new(), Duration::ZERO, 0)
+    }
+
+    /// Build sinks from `ALERT_WEBHOOK_URL`, `TELEGRAM_BOT_TOKEN` + `TELEGRAM_CHAT_ID` and
+    /// `SLACK_WEBHOOK_URL`, whichever are set
+    pub fn from_env(dedup_window: Duration, max_per_minute: usize) -> Self {
+        let mut sinks: Vec<Box<dyn NotifySink>> = Vec::new();
+        if let Ok(url) = std::env::var("ALERT_WEBHOOK_URL") {
+            sinks.push(Box::new(WebhookSink::new(url)));
+        }
+        if let (Ok(bot_token), Ok(chat_id)) =
+            (std::env::var("TELEGRAM_BOT_TOKEN"), std::env::var("TELEGRAM_CHAT_ID"))
+        {
+            sinks.push(Box::new(TelegramSink::new(bot_token, chat_id)));
+        }
+        if let Ok(url) = std::env::var("SLACK_WEBHOOK_URL") {
+            sinks.push(Box::new(SlackSink::new(url)));
+        }
+
+        let names: Vec<&str> = sinks.iter().map(|sink| sink.name()).collect();
+        let names = if names.is_empty() { "none".to_string() } else { names.join(", ") };
+        info!("🔔 Alert sinks: {}", names);
+        Self::new(sinks, dedup_window, max_per_minute)
+    }
+
+    pub async fn notify(&self, alert: Alert) {
+        if self.sinks.is_empty() {
+            return;
+        }
+
+        let now = Instant::now();
+        {
+            let mut throttle = self.throttle.lock().await;
+            if throttle
+                .last_sent
+                .get(&alert.key)
+                .is_some_and(|sent| now.duration_since(*sent) < self.dedup_window)
+            {
+                return;
+            }
+
+            while throttle
+                .recent
+                .front()
+                .is_some_and(|sent| now.duration_since(*sent) >= Duration::from_secs(60))
+            {
+                throttle.recent.pop_front();
+            }
+            if throttle.recent.len() >= self.max_per_minute {
+                warn!(
+                    "🔔 Alert rate limit reached, dropping {} ({})",
+                    alert.kind.as_str(),
+                    alert.key
+                );
+                return;
+            }
+
+            throttle.recent.push_back(now);
+            throttle.last_sent.insert(alert.key.clone(), now);
+            throttle.last_sent.retain(|_, sent| now.duration_since(*sent) < self.dedup_window);
+        }
+
+        let sinks = self.sinks.clone();
+        tokio::spawn(async move {
+            for sink in sinks.iter() {
+                if let Err(e) = sink.send(&alert).await {
+                    error!(
+                        "❌ Failed to deliver {} alert to {}: {}",
+                        alert.kind.as_str(),
+                        sink.name(),
+                        e
+                    );
+                }
+            }
+        });
+    }
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
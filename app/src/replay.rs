@@ -0,0 +1,298 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use alloy::{
+    rpc::json_rpc::{Id, RequestPacket, Response, ResponsePacket, SerializedRequest},
+    transports::{TransportError, TransportErrorKind, TransportFut},
+};
+use eyre::Result;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tower::{Layer, Service};
+
+/// One JSON-RPC call and its answer; fixture files hold one per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcExchange {
+    pub method: String,
+    /// What the call's params must contain to match; `None` matches any call to `method`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+impl RpcExchange {
+    /// A successful call to `method` with any params
+    pub fn ok(method: &str, result: impl Serialize) -> Self {
+        Self {
+            method: method.to_string(),
+            params: None,
+            result: Some(serde_json::to_value(result).expect("serializable result")),
+            error: None,
+        }
+    }
+
+    /// Only match calls whose params contain `params`
+    pub fn with_params(mut self, params: Value) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    fn matches(&self, method: &str, params: &Value) -> bool {
+        self.method == method
+            && self.params.as_ref().is_none_or(|pattern| contains(params, pattern))
+    }
+
+    fn response(&self, id: Id) -> Result<Response, TransportError> {
+        let mut response = serde_json::json!({ "jsonrpc": "2.0", "id": id });
+        match &self.error {
+            Some(error) => response["error"] = error.clone(),
+            None => response["result"] = self.result.clone().unwrap_or(Value::Null),
+        }
+        serde_json::from_value(response)
+            .map_err(|e| TransportErrorKind::custom_str(&format!("Bad fixture response: {}", e)))
+    }
+}
+
+/// `pattern` is `value` with object keys and trailing array items optionally left out. Strings
+/// compare case-insensitively so checksummed and lowercase addresses match.
+fn contains(value: &Value, pattern: &Value) -> bool {
+    match (value, pattern) {
+        (Value::Object(value), Value::Object(pattern)) => pattern
+            .iter()
+            .all(|(key, pattern)| value.get(key).is_some_and(|value| contains(value, pattern))),
+        (Value::Array(value), Value::Array(pattern)) => {
+            pattern.len() <= value.len()
+                && value.iter().zip(pattern).all(|(value, pattern)| contains(value, pattern))
+        }
+        (Value::String(value), Value::String(pattern)) => value.eq_ignore_ascii_case(pattern),
+        _ => value == pattern,
+    }
+}
+
+/// Params of a request as JSON; requests without params are recorded as `[]`
+fn request_params(request: &SerializedRequest) -> Value {
+    request
+        .params()
+        .and_then(|params| serde_json::from_str(params.get()).ok())
+        .unwrap_or_else(|| Value::Array(Vec::new()))
+}
+
+pub fn load_fixture(path: &Path) -> Result<Vec<RpcExchange>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut exchanges = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let exchange = serde_json::from_str(&line)
+            .map_err(|e| eyre::eyre!("{}:{}: {}", path.display(), line_number + 1, e))?;
+        exchanges.push(exchange);
+    }
+    Ok(exchanges)
+}
+
+pub fn write_fixture(path: &Path, exchanges: &[RpcExchange]) -> Result<()> {
+    let mut file = File::create(path)?;
+    for exchange in exchanges {
+        writeln!(file, "{}", serde_json::to_string(exchange)?)?;
+    }
+    Ok(())
+}
+
+/// Appends every request/response pair that goes through the client to a fixture file. Pairs
+/// are written as responses arrive; transport failures are not recorded.
+#[derive(Clone, Default)]
+pub struct RecordingLayer {
+    file: Option<Arc<Mutex<File>>>,
+}
+
+impl RecordingLayer {
+    pub fn new(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        info!("📼 Recording RPC traffic to {}", path.display());
+        Ok(Self { file: Some(Arc::new(Mutex::new(file))) })
+    }
+
+    /// A layer that records nothing
+    pub fn disabled() -> Self {
+        Self { file: None }
+    }
+
+    /// Record to `RPC_RECORD_FILE` when it is set
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("RPC_RECORD_FILE") {
+            Ok(path) => Self::new(Path::new(&path)),
+            Err(_) => Ok(Self::disabled()),
+        }
+    }
+}
+
+impl<S> Layer<S> for RecordingLayer {
+    type Service = RecordingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordingService { inner, file: self.file.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct RecordingService<S> {
+    inner: S,
+    file: Option<Arc<Mutex<File>>>,
+}
+
+impl<S> Service<RequestPacket> for RecordingService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Send
+        + Clone
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let Some(file) = self.file.clone() else {
+            return Box::pin(self.inner.call(request));
+        };
+
+        let calls: Vec<(Id, String, Value)> = request
+            .requests()
+            .iter()
+            .map(|request| {
+                (request.id().clone(), request.method().to_string(), request_params(request))
+            })
+            .collect();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let response = future.await?;
+            let responses = match &response {
+                ResponsePacket::Single(response) => std::slice::from_ref(response),
+                ResponsePacket::Batch(responses) => responses.as_slice(),
+            };
+            for response in responses {
+                let Some((_, method, params)) = calls.iter().find(|(id, ..)| *id == response.id)
+                else {
+                    continue;
+                };
+                let recorded = match serde_json::to_value(response) {
+                    Ok(recorded) => recorded,
+                    Err(e) => {
+                        error!("❌ Failed to serialize {} response for recording: {}", method, e);
+                        continue;
+                    }
+                };
+                let exchange = RpcExchange {
+                    method: method.clone(),
+                    params: Some(params.clone()),
+                    result: recorded.get("result").cloned(),
+                    error: recorded.get("error").cloned(),
+                };
+                let line = serde_json::to_string(&exchange).unwrap_or_default();
+                let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                if let Err(e) = writeln!(file, "{}", line) {
+                    error!("❌ Failed to record {} exchange: {}", method, e);
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Recorded {
+    exchange: RpcExchange,
+    served: bool,
+}
+
+/// Transport answering from recorded exchanges instead of a node. Each call gets the first
+/// matching exchange not served yet, in fixture order; once all matches are used up the last
+/// one keeps answering, so polled calls like `eth_blockNumber` settle on their final value. Calls
+/// with no match fail at the transport level.
+#[derive(Clone)]
+pub struct ReplayTransport {
+    exchanges: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<RpcExchange>) -> Self {
+        let exchanges =
+            exchanges.into_iter().map(|exchange| Recorded { exchange, served: false }).collect();
+        Self { exchanges: Arc::new(Mutex::new(exchanges)) }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(Self::new(load_fixture(path)?))
+    }
+
+    /// Exchanges that have not been served yet
+    pub fn unserved(&self) -> Vec<RpcExchange> {
+        let exchanges = self.exchanges.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        exchanges.iter().filter(|recorded| !recorded.served).map(|r| r.exchange.clone()).collect()
+    }
+
+    fn answer(&self, request: &SerializedRequest) -> Result<Response, TransportError> {
+        let method = request.method();
+        let params = request_params(request);
+        let mut exchanges = self.exchanges.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let matching: Vec<usize> = exchanges
+            .iter()
+            .enumerate()
+            .filter(|(_, recorded)| recorded.exchange.matches(method, &params))
+            .map(|(index, _)| index)
+            .collect();
+        let index = matching
+            .iter()
+            .copied()
+            .find(|index| !exchanges[*index].served)
+            .or_else(|| matching.last().copied())
+            .ok_or_else(|| {
+                TransportErrorKind::custom_str(&format!(
+                    "No recorded exchange for {} {}",
+                    method, params
+                ))
+            })?;
+
+        exchanges[index].served = true;
+        exchanges[index].exchange.response(request.id().clone())
+    }
+}
+
+impl Service<RequestPacket> for ReplayTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let response = match &request {
+            RequestPacket::Single(request) => self.answer(request).map(ResponsePacket::Single),
+            RequestPacket::Batch(requests) => requests
+                .iter()
+                .map(|request| self.answer(request))
+                .collect::<Result<Vec<_>, _>>()
+                .map(ResponsePacket::Batch),
+        };
+        Box::pin(async move { response })
+    }
+}
@@ -0,0 +1,273 @@
+//! The live liquidation path against mock Liquity contracts on a local anvil node. Ignored by
+//! default; run with `cargo test -- --ignored` once `anvil` is installed and the contracts have
+//! been built into `artifacts/` (`forge build --out ../artifacts` from `contracts/`).
+
+use std::{
+    net::TcpListener,
+    path::Path,
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use alloy::{
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::{Address, Bytes, I256, U256},
+    providers::{Provider, ProviderBuilder},
+    rpc::{client::ClientBuilder, types::TransactionRequest},
+    signers::local::PrivateKeySigner,
+    sol,
+    sol_types::SolValue,
+};
+use eyre::Result;
+
+use crate::{
+    AddressRegistry, DefaultProvider,
+    collector::{BlockCollector, LogCollector},
+    db::initialize_database,
+    liquity::{
+        liquidation_recorder::LiquidationRecorder,
+        liquity::TroveManager,
+        liquity_exexcution::LiquityExecutor,
+        liquity_strategy::{LiquityStrategy, StrategyProvider},
+    },
+};
+
+sol! {
+    #[sol(rpc)]
+    interface MockPriceFeed {
+        function setPrice(int256 answer) external;
+    }
+
+    #[sol(rpc)]
+    interface MockTroveManager {
+        function openTrove(
+            uint256 _troveId,
+            uint256 _coll,
+            uint256 _debt,
+            uint256 _annualInterestRate
+        ) external;
+    }
+}
+
+const E18: u128 = 1_000_000_000_000_000_000;
+/// 8-decimal feed answers: 2,000 and 1,500
+const PRICE: i64 = 200_000_000_000;
+const CRASHED_PRICE: i64 = 150_000_000_000;
+/// Anvil's first development account; never holds real funds
+const ANVIL_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcaf784d7bf4f2ff80";
+
+/// An `anvil` process on a free local port, killed on drop
+struct AnvilNode {
+    child: Child,
+    endpoint: String,
+}
+
+impl AnvilNode {
+    async fn spawn() -> Self {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let child = Command::new("anvil")
+            .args(["--port", &port.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("anvil must be installed to run this test");
+        let node = Self { child, endpoint: format!("http://127.0.0.1:{}", port) };
+
+        let provider = ProviderBuilder::new().connect_http(node.endpoint.parse().unwrap());
+        for _ in 0..100 {
+            if provider.get_chain_id().await.is_ok() {
+                return node;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("anvil did not start on port {}", port);
+    }
+}
+
+impl Drop for AnvilNode {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Creation code of a compiled contract
+fn creation_code(file: &str, contract: &str) -> Bytes {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../artifacts")
+        .join(file)
+        .join(format!("{}.json", contract));
+    let artifact = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("{} not built, run forge build first", path.display()));
+    let artifact: serde_json::Value = serde_json::from_str(&artifact).unwrap();
+    Bytes::from_str(artifact["bytecode"]["object"].as_str().unwrap()).unwrap()
+}
+
+async fn deploy(provider: &DefaultProvider, code: &Bytes, args: Vec<u8>) -> Result<Address> {
+    let input: Vec<u8> = code.iter().copied().chain(args).collect();
+    let receipt = provider
+        .send_transaction(TransactionRequest::default().with_deploy_code(input))
+        .await?
+        .get_receipt()
+        .await?;
+    receipt.contract_address.ok_or_else(|| eyre::eyre!("Deployment created no contract"))
+}
+
+/// A mock branch on a fresh anvil node, with the `LiquidationExecutor` owned by the first account
+struct MockBranch {
+    _anvil: AnvilNode,
+    provider: Arc<StrategyProvider>,
+    wallet_provider: Arc<DefaultProvider>,
+    registry: Address,
+    price_feed: Address,
+    liquidator: Address,
+}
+
+impl MockBranch {
+    async fn start() -> Self {
+        let executor_code = creation_code("LiquityLiquidator.sol", "LiquidationExecutor");
+        let price_feed_code = creation_code("LiquityMocks.sol", "MockPriceFeed");
+        let trove_manager_code = creation_code("LiquityMocks.sol", "MockTroveManager");
+        let registry_code = creation_code("LiquityMocks.sol", "MockAddressesRegistry");
+        let anvil = AnvilNode::spawn().await;
+
+        let signer: PrivateKeySigner = ANVIL_KEY.parse().unwrap();
+        let client = || ClientBuilder::default().http(anvil.endpoint.parse().unwrap());
+        let provider = Arc::new(ProviderBuilder::new().connect_client(client()));
+        let wallet_provider = Arc::new(
+            ProviderBuilder::new().wallet(EthereumWallet::from(signer)).connect_client(client()),
+        );
+
+        let mcr = U256::from(11 * E18 / 10);
+        let scr = U256::from(11 * E18 / 10);
+        let price_feed = deploy(
+            &wallet_provider,
+            &price_feed_code,
+            (I256::try_from(PRICE).unwrap(),).abi_encode_params(),
+        )
+        .await
+        .unwrap();
+        let trove_manager =
+            deploy(&wallet_provider, &trove_manager_code, (price_feed, mcr).abi_encode_params())
+                .await
+                .unwrap();
+        let registry = deploy(
+            &wallet_provider,
+            &registry_code,
+            (trove_manager, price_feed, mcr, scr).abi_encode_params(),
+        )
+        .await
+        .unwrap();
+        let liquidator = deploy(&wallet_provider, &executor_code, Vec::new()).await.unwrap();
+
+        Self { _anvil: anvil, provider, wallet_provider, registry, price_feed, liquidator }
+    }
+
+    async fn open_trove(&self, trove_manager: Address, id: u64, coll: u128, debt: u128) {
+        MockTroveManager::new(trove_manager, &*self.wallet_provider)
+            .openTrove(U256::from(id), U256::from(coll), U256::from(debt), U256::from(E18 / 20))
+            .send()
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+    }
+
+    async fn set_price(&self, answer: i64) {
+        MockPriceFeed::new(self.price_feed, &*self.wallet_provider)
+            .setPrice(I256::try_from(answer).unwrap())
+            .send()
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and forge-built artifacts"]
+async fn liquidates_troves_under_mcr_on_anvil() {
+    let branch = MockBranch::start().await;
+
+    // Branch addresses come from the registry, as in `App::connect`
+    let registry = AddressRegistry::new(branch.registry, &*branch.provider);
+    let mcr = registry.MCR().call().await.unwrap();
+    let trove_manager = registry.troveManager().call().await.unwrap();
+
+    let path = std::env::temp_dir().join(format!("liquidator-{}-anvil.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = initialize_database(&format!("sqlite:{}", path.display())).await.unwrap();
+
+    let executor = LiquityExecutor::new(
+        branch.liquidator,
+        trove_manager,
+        branch.wallet_provider.clone(),
+        branch.provider.clone(),
+    );
+    let recorder = LiquidationRecorder::new(
+        trove_manager,
+        branch.liquidator,
+        None,
+        store.clone(),
+        branch.provider.clone(),
+    );
+    let strategy = LiquityStrategy::new(
+        trove_manager,
+        store.clone(),
+        branch.provider.clone(),
+        branch.price_feed,
+        mcr,
+        executor,
+        recorder,
+    )
+    .await;
+
+    // Trove 1 sits at 133% and drops to 100% when the price falls; trove 2 never goes below 300%
+    branch.open_trove(trove_manager, 1, 10 * E18, 15_000 * E18).await;
+    branch.open_trove(trove_manager, 2, 10 * E18, 5_000 * E18).await;
+
+    let mut log_collector = LogCollector::new();
+    log_collector.set_contract_address(trove_manager);
+    log_collector.set_start_block(0);
+    log_collector.connect_provider(branch.provider.clone()).await;
+    log_collector._add_strategy(Box::new(strategy.clone())).await;
+    log_collector.start_listening_with_history().await.unwrap();
+    for id in ["1", "2"] {
+        let trove = store.get_trove_by_id(id).await.unwrap().unwrap();
+        assert_eq!(trove.status, "active");
+    }
+
+    branch.set_price(CRASHED_PRICE).await;
+    let block_provider: Arc<dyn Provider> = branch.provider.clone();
+    let block_collector = BlockCollector::new();
+    block_collector.add_strategy(Box::new(strategy)).await;
+    block_collector.poll_once(&block_provider).await.unwrap();
+
+    let trove_manager_instance = TroveManager::new(trove_manager, &*branch.provider);
+    let status = trove_manager_instance.getTroveStatus(U256::from(1)).call().await.unwrap();
+    assert_eq!(status, 3, "trove 1 is closedByLiquidation on-chain");
+    let status = trove_manager_instance.getTroveStatus(U256::from(2)).call().await.unwrap();
+    assert_eq!(status, 1, "trove 2 stays active on-chain");
+
+    let candidate = store.get_liquidation_candidate("1").await.unwrap().unwrap();
+    assert!(candidate.attempted);
+    let tx_hash = candidate.attempt_tx.expect("liquidation tx recorded");
+    assert!(store.get_liquidation_candidate("2").await.unwrap().is_none());
+
+    // The next block ingests the liquidation's own events
+    block_collector.poll_once(&block_provider).await.unwrap();
+    let trove = store.get_trove_by_id("1").await.unwrap().unwrap();
+    assert_eq!(trove.status, "closed_by_liquidation");
+    let trove = store.get_trove_by_id("2").await.unwrap().unwrap();
+    assert_eq!(trove.status, "active");
+
+    let liquidations = store.get_liquidations_by_tx(&tx_hash).await.unwrap();
+    assert_eq!(liquidations.len(), 1);
+    assert!(liquidations[0].is_ours);
+    assert_eq!(liquidations[0].coll_gas_compensation, (5 * E18 / 100).to_string());
+    assert_eq!(store.get_troves_liquidated_in_tx(&tx_hash).await.unwrap(), vec!["1"]);
+}
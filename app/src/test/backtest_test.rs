@@ -0,0 +1,140 @@
+//! Backtests replayed from a recorded stream with a known on-chain liquidation
+
+use std::path::PathBuf;
+
+use alloy::{
+    primitives::{Address, B256, Log as PrimitiveLog, U256, address},
+    rpc::types::Log,
+    sol_types::SolEvent,
+};
+use serde_json::{Value, json};
+
+use crate::{
+    config::CandidateSource,
+    liquity::{
+        backtest::{BacktestConfig, BacktestReport, Backtester, SimulatedExecutor, load_records},
+        liquity::{TroveManager, operation},
+    },
+};
+
+const TROVE_MANAGER: Address = address!("0x0000000000000000000000000000000000000a11");
+const E18: u128 = 1_000_000_000_000_000_000;
+const TIMESTAMP: u64 = 1_700_000_000;
+/// Found by the strategy once the price drops, then liquidated on-chain two blocks later
+const UNDERWATER: u64 = 1;
+const HEALTHY: u64 = 2;
+/// Liquidated on-chain while the ICR model still saw it above MCR
+const MISSED: u64 = 3;
+
+fn e18(amount: u64) -> U256 {
+    U256::from(amount) * U256::from(E18)
+}
+
+fn log_record(block_number: u64, data: alloy::primitives::LogData) -> Value {
+    let log = Log {
+        inner: PrimitiveLog { address: TROVE_MANAGER, data },
+        block_number: Some(block_number),
+        block_timestamp: Some(TIMESTAMP + (block_number - 10) * 12),
+        transaction_hash: Some(B256::repeat_byte(block_number as u8)),
+        log_index: Some(0),
+        ..Default::default()
+    };
+    json!({ "type": "log", "log": log })
+}
+
+fn trove_updated(block_number: u64, trove_id: u64, debt: U256, coll: U256) -> Value {
+    let event = TroveManager::TroveUpdated {
+        _troveId: U256::from(trove_id),
+        _debt: debt,
+        _coll: coll,
+        _annualInterestRate: U256::from(5 * E18 / 100),
+        ..Default::default()
+    };
+    log_record(block_number, event.encode_log_data())
+}
+
+fn liquidated(block_number: u64, trove_id: u64) -> [Value; 2] {
+    let event = TroveManager::TroveOperation {
+        _troveId: U256::from(trove_id),
+        _operation: operation::LIQUIDATE,
+        ..Default::default()
+    };
+    [
+        trove_updated(block_number, trove_id, U256::ZERO, U256::ZERO),
+        log_record(block_number, event.encode_log_data()),
+    ]
+}
+
+fn price(kind: &str, block: u64, price: u64) -> Value {
+    let timestamp = TIMESTAMP + (block - 10) * 12;
+    json!({ "type": kind, "block": block, "timestamp": timestamp, "price": e18(price) })
+}
+
+/// Three troves opened at 3,000; the price drops to 2,000 at block 20
+fn stream() -> Vec<Value> {
+    let mut records = vec![
+        price("price", 10, 3_000),
+        price("native_price", 10, 3_000),
+        trove_updated(10, UNDERWATER, e18(20_000), e18(10)),
+        trove_updated(10, HEALTHY, e18(5_000), e18(10)),
+        trove_updated(10, MISSED, e18(16_000), e18(10)),
+        price("price", 20, 2_000),
+    ];
+    records.extend(liquidated(22, UNDERWATER));
+    records.extend(liquidated(25, MISSED));
+    records
+}
+
+fn write_stream(name: &str, records: &[Value]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("backtest-{}-{}", std::process::id(), name));
+    let lines: Vec<String> = records.iter().map(Value::to_string).collect();
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    path
+}
+
+fn run(name: &str, source: CandidateSource) -> BacktestReport {
+    let path = write_stream(&format!("{}-{:?}.jsonl", name, source), &stream());
+    let records = load_records(&path).unwrap();
+    Backtester::new(BacktestConfig {
+        mcr: U256::from(11 * E18 / 10),
+        candidate_source: source,
+        liquidation_price_horizon_secs: 3_600,
+        executor: SimulatedExecutor {
+            gas_per_batch: 150_000,
+            gas_per_trove: 100_000,
+            gas_price: 1_000_000_000,
+        },
+    })
+    .run(&records)
+    .unwrap()
+}
+
+#[test]
+fn backtest_finds_a_known_liquidation_from_every_source() {
+    let sources =
+        [CandidateSource::PriceIndex, CandidateSource::Database, CandidateSource::SortedTroves];
+    for source in sources {
+        let report = run("every-source", source);
+        assert_eq!((report.blocks, report.logs, report.price_updates), (4, 7, 3));
+
+        assert_eq!(report.liquidations.len(), 1, "{:?}", source);
+        let liquidation = &report.liquidations[0];
+        assert_eq!(liquidation.trove_id, UNDERWATER.to_string());
+        assert_eq!(liquidation.eligible_block, 20);
+        assert_eq!(liquidation.found_block, 20);
+        assert_eq!(liquidation.actual_block, Some(22));
+        assert_eq!(liquidation.gas_used, 250_000);
+
+        assert_eq!(report.missed.len(), 1);
+        assert_eq!(report.missed[0].trove_id, MISSED.to_string());
+        assert_eq!(report.missed[0].eligible_block, None);
+        assert_eq!(report.missed[0].actual_block, 25);
+    }
+}
+
+#[test]
+fn backtest_values_weth_compensation_and_gas_at_the_native_price() {
+    // 0.05 coll at 2,000 plus 0.0375 WETH at 3,000, less 250,000 gas at 1 gwei and 3,000
+    let profit = run("native-price", CandidateSource::PriceIndex).total_profit_usd();
+    assert!((profit - 211.75).abs() < 1e-9, "profit {}", profit);
+}
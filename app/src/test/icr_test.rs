@@ -0,0 +1,161 @@
+//! `calculate_full_icr` and `calc_interest` against a reference model of the Solidity formulas
+
+use alloy::primitives::U256;
+use quickcheck::{Arbitrary, Gen, quickcheck};
+
+use crate::liquity::liquity_strategy::LiquityStrategy;
+
+const ONE_YEAR: u64 = 31_536_000;
+const DECIMAL_PRECISION: u128 = 1_000_000_000_000_000_000;
+/// Liquity v2's `MAX_ANNUAL_INTEREST_RATE` (250%)
+const MAX_ANNUAL_INTEREST_RATE: u128 = 2_500_000_000_000_000_000;
+
+/// `TroveManager._calcInterest` with Solidity's checked arithmetic; `None` where it reverts
+fn reference_interest(weighted_debt: U256, period: U256) -> Option<U256> {
+    Some(weighted_debt.checked_mul(period)? / U256::from(ONE_YEAR) / U256::from(DECIMAL_PRECISION))
+}
+
+/// `TroveManager.getCurrentICR` for a trove with no redistribution gains or batch manager, via
+/// `LiquityMath._computeCR`; `None` where the contract reverts
+fn reference_icr(debt: U256, coll: U256, rate: U256, period: u64, price: U256) -> Option<U256> {
+    let weighted_recorded_debt = debt.checked_mul(rate)?;
+    let accrued_interest = reference_interest(weighted_recorded_debt, U256::from(period))?;
+    let entire_debt = debt.checked_add(accrued_interest)?;
+    if entire_debt.is_zero() {
+        return Some(U256::MAX);
+    }
+    Some(coll.checked_mul(price)? / entire_debt)
+}
+
+/// A token amount, price or rate drawn from edge cases, realistic 18-decimal values and the
+/// full 256-bit range
+#[derive(Debug, Clone, Copy)]
+struct Value(U256);
+
+impl Arbitrary for Value {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let value = match u8::arbitrary(g) % 5 {
+            0 => *g.choose(&[U256::ZERO, U256::from(1), U256::MAX]).unwrap(),
+            1 => U256::from(u64::arbitrary(g)),
+            2 => U256::from(u128::arbitrary(g) % (1_000_000_000 * DECIMAL_PRECISION)),
+            3 => U256::from(u128::arbitrary(g)),
+            _ => U256::from_limbs([
+                u64::arbitrary(g),
+                u64::arbitrary(g),
+                u64::arbitrary(g),
+                u64::arbitrary(g),
+            ]),
+        };
+        Value(value)
+    }
+}
+
+/// An annual rate, mostly within the protocol's bounds
+#[derive(Debug, Clone, Copy)]
+struct Rate(U256);
+
+impl Arbitrary for Rate {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if u8::arbitrary(g) % 4 == 0 {
+            Rate(Value::arbitrary(g).0)
+        } else {
+            Rate(U256::from(u128::arbitrary(g) % (MAX_ANNUAL_INTEREST_RATE + 1)))
+        }
+    }
+}
+
+/// Seconds since the last debt update, up to far beyond any real trove's age
+#[derive(Debug, Clone, Copy)]
+struct Period(u64);
+
+impl Arbitrary for Period {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let period = match u8::arbitrary(g) % 4 {
+            0 => *g.choose(&[0, 1, ONE_YEAR, 100 * ONE_YEAR, u64::MAX]).unwrap(),
+            1 => u64::arbitrary(g) % (10 * ONE_YEAR),
+            _ => u64::arbitrary(g),
+        };
+        Period(period)
+    }
+}
+
+fn model_icr(debt: U256, coll: U256, rate: U256, period: u64, price: U256) -> U256 {
+    LiquityStrategy::calculate_full_icr(debt, coll, rate, period, 0, price)
+}
+
+quickcheck! {
+    fn interest_matches_reference(weighted_debt: Value, period: Period) -> bool {
+        let period = U256::from(period.0);
+        let model = LiquityStrategy::calc_interest(weighted_debt.0, period);
+        reference_interest(weighted_debt.0, period).is_none_or(|reference| model == reference)
+    }
+
+    fn icr_matches_reference(
+        debt: Value,
+        coll: Value,
+        rate: Rate,
+        period: Period,
+        price: Value
+    ) -> bool {
+        let model = model_icr(debt.0, coll.0, rate.0, period.0, price.0);
+        reference_icr(debt.0, coll.0, rate.0, period.0, price.0)
+            .is_none_or(|reference| model == reference)
+    }
+
+    fn icr_never_rises_with_time(
+        debt: Value,
+        coll: Value,
+        rate: Rate,
+        period: Period,
+        more: Period,
+        price: Value
+    ) -> bool {
+        let later = period.0.saturating_add(more.0);
+        model_icr(debt.0, coll.0, rate.0, later, price.0)
+            <= model_icr(debt.0, coll.0, rate.0, period.0, price.0)
+    }
+
+    fn zero_debt_is_infinite_icr(coll: Value, rate: Rate, period: Period, price: Value) -> bool {
+        model_icr(U256::ZERO, coll.0, rate.0, period.0, price.0) == U256::MAX
+    }
+
+    fn uses_elapsed_time_only(
+        debt: Value,
+        coll: Value,
+        rate: Rate,
+        period: Period,
+        price: Value
+    ) -> bool {
+        let start = 1_700_000_000;
+        LiquityStrategy::calculate_full_icr(
+            debt.0,
+            coll.0,
+            rate.0,
+            start + period.0 % (100 * ONE_YEAR),
+            start,
+            price.0,
+        ) == model_icr(debt.0, coll.0, rate.0, period.0 % (100 * ONE_YEAR), price.0)
+    }
+}
+
+#[test]
+fn icr_of_realistic_trove() {
+    let e18 = U256::from(DECIMAL_PRECISION);
+    // 10 ETH against 15,000 BOLD at 5% for a year: 15,750 debt, so 126.98% at 2,000
+    let icr = model_icr(
+        U256::from(15_000) * e18,
+        U256::from(10) * e18,
+        U256::from(5) * e18 / U256::from(100),
+        ONE_YEAR,
+        U256::from(2_000) * e18,
+    );
+    assert_eq!(icr, U256::from(1_269_841_269_841_269_841u128));
+}
+
+#[test]
+fn overflowing_collateral_value_saturates() {
+    assert_eq!(model_icr(U256::from(1), U256::MAX, U256::ZERO, 0, U256::MAX), U256::MAX);
+    // Exact where `coll * price` overflows but the ratio fits
+    let icr = model_icr(U256::MAX, U256::MAX, U256::ZERO, 0, U256::from(3));
+    assert_eq!(icr, U256::from(3));
+}
@@ -1,3 +1,263 @@
-// src/lib.rs or src/main.rs
+//! End-to-end flows served from recorded RPC exchanges, so they run offline
 
+use std::{path::PathBuf, sync::Arc};
 
+use alloy::{
+    network::EthereumWallet,
+    primitives::{Address, B256, U256, address},
+    providers::{Provider, ProviderBuilder},
+    rpc::{client::ClientBuilder, types::Log},
+    signers::local::PrivateKeySigner,
+    sol_types::SolEvent,
+};
+use serde_json::json;
+
+use crate::{
+    DefaultProvider,
+    collector::{BlockCollector, LogCollector},
+    config::CandidateSource,
+    db::{DatabaseStore, initialize_database},
+    liquity::{
+        liquidation_recorder::LiquidationRecorder,
+        liquity::TroveManager,
+        liquity_exexcution::LiquityExecutor,
+        liquity_strategy::{LiquityStrategy, StrategyProvider},
+    },
+    replay::{RecordingLayer, ReplayTransport, RpcExchange, load_fixture, write_fixture},
+};
+
+const TROVE_MANAGER: Address = address!("0x0000000000000000000000000000000000000a11");
+const ORACLE: Address = address!("0x0000000000000000000000000000000000000b22");
+const LIQUIDATOR: Address = address!("0x0000000000000000000000000000000000000c33");
+/// Well-known anvil development key; never holds real funds
+const TEST_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcaf784d7bf4f2ff80";
+
+const E18: u128 = 1_000_000_000_000_000_000;
+const TROVE_ID: u64 = 42;
+const LOG_TIMESTAMP: u64 = 1_700_000_000;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("liquidator-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn test_store(name: &str) -> Arc<dyn DatabaseStore> {
+    let path = temp_path(&format!("{}.db", name));
+    initialize_database(&format!("sqlite:{}", path.display())).await.unwrap()
+}
+
+fn provider(replay: &ReplayTransport) -> Arc<StrategyProvider> {
+    let client = ClientBuilder::default().transport(replay.clone(), true);
+    Arc::new(ProviderBuilder::new().connect_client(client))
+}
+
+fn wallet_provider(replay: &ReplayTransport) -> Arc<DefaultProvider> {
+    let signer: PrivateKeySigner = TEST_KEY.parse().unwrap();
+    let client = ClientBuilder::default().transport(replay.clone(), true);
+    Arc::new(ProviderBuilder::new().wallet(EthereumWallet::from(signer)).connect_client(client))
+}
+
+/// ABI encoding of a tuple of static words
+fn words(values: &[U256]) -> String {
+    let encoded: Vec<String> =
+        values.iter().map(|value| format!("{:x}", B256::from(*value))).collect();
+    format!("0x{}", encoded.concat())
+}
+
+/// An underwater trove: 10 coll and 20,000 debt, which is a 100% ICR at a 2,000 price
+fn trove_updated_log(block_number: u64) -> Log {
+    let event = TroveManager::TroveUpdated {
+        _troveId: U256::from(TROVE_ID),
+        _debt: U256::from(20_000 * E18),
+        _coll: U256::from(10 * E18),
+        _annualInterestRate: U256::from(5 * E18 / 100),
+        ..Default::default()
+    };
+    Log {
+        inner: alloy::primitives::Log { address: TROVE_MANAGER, data: event.encode_log_data() },
+        block_number: Some(block_number),
+        block_timestamp: Some(LOG_TIMESTAMP),
+        transaction_hash: Some(B256::repeat_byte(0x11)),
+        log_index: Some(0),
+        ..Default::default()
+    }
+}
+
+fn block(number: u64, timestamp: u64) -> serde_json::Value {
+    let mut block = alloy::rpc::types::Block::<alloy::rpc::types::Transaction>::default();
+    block.header.inner.number = number;
+    block.header.inner.timestamp = timestamp;
+    serde_json::to_value(block).unwrap()
+}
+
+/// What a node answers while a trove is ingested at block 95 and found liquidatable at 101
+fn liquidation_flow() -> Vec<RpcExchange> {
+    let mut latest_trove_data = vec![U256::ZERO; 10];
+    latest_trove_data[0] = U256::from(20_000 * E18);
+    latest_trove_data[1] = U256::from(10 * E18);
+
+    vec![
+        // LogCollector backfill up to head 100, then BlockCollector sees head 101
+        RpcExchange::ok("eth_blockNumber", "0x64"),
+        RpcExchange::ok("eth_blockNumber", "0x65"),
+        RpcExchange::ok("eth_getLogs", vec![trove_updated_log(95)]),
+        RpcExchange::ok("eth_getLogs", Vec::<Log>::new()),
+        RpcExchange::ok("eth_getBlockByNumber", block(101, LOG_TIMESTAMP + 72)),
+        // latestAnswer() = 2,000 (8 decimals), then latestTimestamp()
+        RpcExchange::ok("eth_call", words(&[U256::from(200_000_000_000u64)]))
+            .with_params(json!([{ "to": ORACLE.to_string() }])),
+        RpcExchange::ok("eth_call", words(&[U256::from(LOG_TIMESTAMP + 60)]))
+            .with_params(json!([{ "to": ORACLE.to_string() }])),
+        RpcExchange::ok("eth_estimateGas", "0x3d090"),
+        RpcExchange::ok("eth_call", words(&latest_trove_data))
+            .with_params(json!([{ "to": TROVE_MANAGER.to_string() }])),
+    ]
+}
+
+async fn shadow_strategy(
+    replay: &ReplayTransport,
+    store: Arc<dyn DatabaseStore>,
+    source: CandidateSource,
+) -> LiquityStrategy {
+    let provider = provider(replay);
+    let mut executor =
+        LiquityExecutor::new(LIQUIDATOR, TROVE_MANAGER, wallet_provider(replay), provider.clone());
+    executor.set_shadow_mode(store.clone());
+    let recorder =
+        LiquidationRecorder::new(TROVE_MANAGER, LIQUIDATOR, None, store.clone(), provider.clone());
+    let mut strategy = LiquityStrategy::new(
+        TROVE_MANAGER,
+        store,
+        provider,
+        ORACLE,
+        U256::from(11 * E18 / 10),
+        executor,
+        recorder,
+    )
+    .await;
+    strategy.set_candidate_source(source);
+    strategy
+}
+
+#[tokio::test]
+async fn replay_serves_exchanges_in_order_and_repeats_the_last() {
+    let replay = ReplayTransport::new(vec![
+        RpcExchange::ok("eth_blockNumber", "0x1"),
+        RpcExchange::ok("eth_blockNumber", "0x2"),
+    ]);
+    let provider = provider(&replay);
+
+    assert_eq!(provider.get_block_number().await.unwrap(), 1);
+    assert_eq!(provider.get_block_number().await.unwrap(), 2);
+    assert_eq!(provider.get_block_number().await.unwrap(), 2);
+    assert!(replay.unserved().is_empty());
+    assert!(provider.get_chain_id().await.is_err());
+}
+
+#[tokio::test]
+async fn replay_matches_params_by_containment() {
+    let replay = ReplayTransport::new(vec![
+        RpcExchange::ok("eth_getBalance", "0x1").with_params(json!(["0xABC"])),
+        RpcExchange::ok("eth_getBalance", "0x2").with_params(json!(["0xdef", "latest"])),
+    ]);
+    let provider = provider(&replay);
+
+    let balance: String =
+        provider.raw_request("eth_getBalance".into(), json!(["0xdef", "latest"])).await.unwrap();
+    assert_eq!(balance, "0x2");
+    let balance: String =
+        provider.raw_request("eth_getBalance".into(), json!(["0xabc", "latest"])).await.unwrap();
+    assert_eq!(balance, "0x1");
+    assert!(
+        provider
+            .raw_request::<_, String>("eth_getBalance".into(), json!(["0x123"]))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn recorded_session_replays_identically() {
+    let fixture = temp_path("recorded.jsonl");
+    let live = ReplayTransport::new(vec![
+        RpcExchange::ok("eth_chainId", "0x3e7"),
+        RpcExchange::ok("eth_blockNumber", "0x10"),
+    ]);
+    let client = ClientBuilder::default()
+        .layer(RecordingLayer::new(&fixture).unwrap())
+        .transport(live, true);
+    let recording = ProviderBuilder::new().connect_client(client);
+    let chain_id = recording.get_chain_id().await.unwrap();
+    let block_number = recording.get_block_number().await.unwrap();
+
+    let recorded = load_fixture(&fixture).unwrap();
+    assert_eq!(recorded.len(), 2);
+    assert!(recorded.iter().all(|exchange| exchange.params.is_some()));
+
+    let replayed = provider(&ReplayTransport::from_file(&fixture).unwrap());
+    assert_eq!(replayed.get_chain_id().await.unwrap(), chain_id);
+    assert_eq!(replayed.get_block_number().await.unwrap(), block_number);
+}
+
+/// Ingest the trove, find it liquidatable at block 101 from `source` and record it in shadow
+/// mode; returns the store
+async fn shadow_liquidation_flow(name: &str, source: CandidateSource) -> Arc<dyn DatabaseStore> {
+    let fixture = temp_path(&format!("{}.jsonl", name));
+    write_fixture(&fixture, &liquidation_flow()).unwrap();
+    let replay = ReplayTransport::from_file(&fixture).unwrap();
+    let store = test_store(name).await;
+    let strategy = shadow_strategy(&replay, store.clone(), source).await;
+
+    let mut log_collector = LogCollector::new();
+    log_collector.set_contract_address(TROVE_MANAGER);
+    log_collector.set_start_block(90);
+    log_collector.connect_provider(provider(&replay)).await;
+    log_collector._add_strategy(Box::new(strategy.clone())).await;
+    assert_eq!(log_collector.start_listening_with_history().await.unwrap(), 100);
+
+    let trove = store.get_trove_by_id(&TROVE_ID.to_string()).await.unwrap().unwrap();
+    assert_eq!(trove.status, "active");
+    assert_eq!(trove.last_updated, 95);
+    assert_eq!(trove.last_updated_at, LOG_TIMESTAMP as i64);
+
+    let block_provider: Arc<dyn Provider> = provider(&replay);
+    let block_collector = BlockCollector::new();
+    block_collector.add_strategy(Box::new(strategy)).await;
+    assert_eq!(block_collector.poll_once(&block_provider).await.unwrap(), 101);
+    assert_eq!(store.get_last_block().await.unwrap(), 101);
+
+    let shadow = store.get_shadow_comparison(10).await.unwrap();
+    assert_eq!(shadow.len(), 1);
+    assert_eq!(shadow[0].trove_id, TROVE_ID.to_string());
+    assert_eq!(shadow[0].shadow_block, 101);
+    assert!(shadow[0].simulated_ok);
+    assert_eq!(shadow[0].estimated_gas, Some(250_000));
+    // 0.5% of 10 coll
+    assert_eq!(shadow[0].expected_coll_compensation, (5 * E18 / 100).to_string());
+    assert!(replay.unserved().is_empty(), "unserved: {:?}", replay.unserved());
+    store
+}
+
+#[tokio::test]
+async fn backfill_then_shadow_liquidate_from_fixture() {
+    let store = shadow_liquidation_flow("liquidation-flow", CandidateSource::Database).await;
+
+    // Deciding again later neither re-simulates nor moves the recorded decision block
+    let offline = ReplayTransport::new(Vec::new());
+    let wallet = wallet_provider(&offline);
+    let mut executor = LiquityExecutor::new(LIQUIDATOR, TROVE_MANAGER, wallet, provider(&offline));
+    executor.set_shadow_mode(store.clone());
+    assert_eq!(executor.execute(vec![U256::from(TROVE_ID)], 102).await.unwrap(), None);
+    assert_eq!(store.get_shadow_comparison(10).await.unwrap()[0].shadow_block, 101);
+}
+
+#[tokio::test]
+async fn price_index_finds_the_fixture_liquidation() {
+    let store = shadow_liquidation_flow("price-index-flow", CandidateSource::PriceIndex).await;
+
+    // 1.1 * 20,000 debt (plus interest accrued to the horizon) over 10 coll
+    let trove = store.get_trove_by_id(&TROVE_ID.to_string()).await.unwrap().unwrap();
+    assert!(trove.liquidation_price.0 > U256::from(2_200 * E18));
+    assert!(trove.liquidation_price.0 < U256::from(2_201 * E18));
+}
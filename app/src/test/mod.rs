@@ -0,0 +1,6 @@
+mod anvil_test;
+mod backtest_test;
+mod icr_test;
+mod liquidator_test;
+mod store_test;
+mod strategy_test;
@@ -0,0 +1,386 @@
+//! One suite over the `DatabaseStore` contract, run against every backend. The PostgreSQL run is
+//! ignored by default: run it with `--ignored` and `TEST_POSTGRES_URL` pointing at a scratch
+//! database, as it drops the bot's tables first.
+
+use std::sync::Arc;
+
+use alloy::primitives::U256;
+
+use crate::db::{
+    DatabaseStore, initialize_database,
+    migrations::{latest_version, migrate},
+    sqlite::SqliteStore,
+    store::{
+        LiquidatedTroveInsert, LiquidationInsert, ShadowLiquidationInsert, Trove,
+        TroveEventInsert, TroveStatus,
+    },
+};
+
+const E18: u128 = 1_000_000_000_000_000_000;
+/// 2023-11-14 22:13:20 UTC
+const TIMESTAMP: i64 = 1_700_000_000;
+const TABLES: &[&str] = &[
+    "schema_version",
+    "last_block",
+    "troves",
+    "trove_events",
+    "liquidations",
+    "liquidation_candidates",
+    "liquidated_troves",
+    "shadow_liquidations",
+];
+
+/// A trove with `rate` in percent
+fn trove(id: &str, coll: U256, debt: U256, rate: u64, status: TroveStatus) -> Trove {
+    let rate = U256::from(rate) * U256::from(E18 / 100);
+    Trove {
+        trove_id: id.to_string(),
+        collateral: coll.into(),
+        debt: debt.into(),
+        icr_key: Trove::icr_key(coll, debt),
+        interest_rate: rate.into(),
+        status: status.as_str().to_string(),
+        last_updated: 10,
+        last_updated_at: TIMESTAMP,
+        liquidation_price: U256::ZERO.into(),
+        liquidation_price_until: 0,
+    }
+}
+
+fn event(block: i64, tx_hash: &str, log_index: i64, operation: &str) -> TroveEventInsert {
+    TroveEventInsert {
+        trove_id: "1".to_string(),
+        block_number: block,
+        tx_hash: tx_hash.to_string(),
+        log_index,
+        operation: operation.to_string(),
+        annual_interest_rate: (5 * E18 / 100).to_string(),
+        debt_increase_from_redist: "0".to_string(),
+        debt_increase_from_upfront_fee: "0".to_string(),
+        debt_change_from_operation: (1_000 * E18).to_string(),
+        coll_increase_from_redist: "0".to_string(),
+        coll_change_from_operation: "0".to_string(),
+    }
+}
+
+fn liquidation(tx_hash: &str, is_ours: bool) -> LiquidationInsert {
+    LiquidationInsert {
+        branch: "0xbranch".to_string(),
+        block_number: 30,
+        block_timestamp: TIMESTAMP,
+        tx_hash: tx_hash.to_string(),
+        log_index: 1,
+        sender: if is_ours { "0xours" } else { "0xother" }.to_string(),
+        is_ours,
+        debt_offset_by_sp: (15_000 * E18).to_string(),
+        debt_redistributed: "0".to_string(),
+        coll_gas_compensation: (5 * E18 / 100).to_string(),
+        bold_gas_compensation: (200 * E18).to_string(),
+        coll_sent_to_sp: (995 * E18 / 100).to_string(),
+        coll_redistributed: "0".to_string(),
+        coll_surplus: "0".to_string(),
+        price: (2_000 * E18).to_string(),
+        gas_used: 300_000,
+        effective_gas_price: "1000000000".to_string(),
+        gas_cost: "300000000000000".to_string(),
+        net_profit_coll: (4 * E18 / 100).to_string(),
+        net_profit_usd: (50 * E18).to_string(),
+        gas_compensation_usd_numeric: 100.0,
+        net_profit_usd_numeric: 50.0,
+    }
+}
+
+/// A trove liquidated by a competitor; `since` is when our model flagged it, if it did
+fn liquidated(
+    trove_id: &str,
+    tx_hash: &str,
+    block: i64,
+    since: Option<i64>,
+) -> LiquidatedTroveInsert {
+    LiquidatedTroveInsert {
+        trove_id: trove_id.to_string(),
+        tx_hash: tx_hash.to_string(),
+        block_number: block,
+        sender: "0xother".to_string(),
+        is_ours: false,
+        effective_gas_price: "2000000000".to_string(),
+        gas_price_gwei: 2.0,
+        liquidatable_since_block: since,
+        blocks_after_eligible: since.map(|since| block - since),
+        we_attempted: since.is_some(),
+    }
+}
+
+fn ids(troves: &[Trove]) -> Vec<&str> {
+    troves.iter().map(|trove| trove.trove_id.as_str()).collect()
+}
+
+async fn exercise_store(store: Arc<dyn DatabaseStore>) {
+    store.ping().await.unwrap();
+
+    // Checkpoint
+    assert_eq!(store.get_last_block().await.unwrap(), 0);
+    store.set_last_block(100).await.unwrap();
+    store.set_last_block(200).await.unwrap();
+    assert_eq!(store.get_last_block().await.unwrap(), 200);
+
+    // Troves: ICR, rate and debt orderings all come from the byte encoding of the amounts, and
+    // trove 3's collateral needs the full 256 bits
+    let e18 = U256::from(E18);
+    let huge = U256::from(1) << 200;
+    // Looked up before any trove exists, so the statement is described right after migrating
+    assert!(store.get_trove_by_id("1").await.unwrap().is_none());
+    let troves = [
+        trove("1", U256::from(10) * e18, U256::from(15_000) * e18, 5, TroveStatus::Active),
+        trove("2", U256::from(20) * e18, U256::from(10_000) * e18, 2, TroveStatus::Active),
+        trove("3", huge, e18, 10, TroveStatus::Active),
+        trove("4", e18, e18, 1, TroveStatus::ClosedByOwner),
+    ];
+    for trove in &troves {
+        store.upsert_trove(trove).await.unwrap();
+    }
+    store.upsert_trove(&troves[0]).await.unwrap();
+
+    assert_eq!(ids(&store.get_lowest_icr_troves(10).await.unwrap()), ["1", "2", "3"]);
+    assert_eq!(ids(&store.get_lowest_icr_troves(2).await.unwrap()), ["1", "2"]);
+    assert_eq!(ids(&store.get_troves_by_interest_rate(10).await.unwrap()), ["2", "1", "3"]);
+    assert_eq!(ids(&store.get_troves_by_debt(2).await.unwrap()), ["1", "2"]);
+    assert_eq!(store.get_open_troves().await.unwrap().len(), 3);
+    assert_eq!(ids(&store.get_reconcilable_troves_after("1", 10).await.unwrap()), ["2", "3"]);
+
+    let stored = store.get_trove_by_id("3").await.unwrap().unwrap();
+    assert_eq!(stored.collateral(), huge);
+    assert_eq!(stored.icr_key.0, huge);
+    assert!(store.get_trove_by_id("5").await.unwrap().is_none());
+
+    // 1,650.5 plus one wei, which a float would not round-trip
+    let liquidation_price = U256::from(1_650_500 * (E18 / 1_000) + 1);
+    let until = TIMESTAMP + 3_600;
+    store.set_liquidation_price("1", TIMESTAMP + 60, liquidation_price, until).await.unwrap();
+    let stored = store.get_trove_by_id("1").await.unwrap().unwrap();
+    assert_eq!(stored.last_updated_at, TIMESTAMP + 60);
+    assert_eq!(stored.liquidation_price.0, liquidation_price);
+    assert_eq!(stored.liquidation_price_until, TIMESTAMP + 3_600);
+
+    store.set_trove_status("3", TroveStatus::Zombie, 50).await.unwrap();
+    let stored = store.get_trove_by_id("3").await.unwrap().unwrap();
+    assert_eq!(TroveStatus::parse(&stored.status), Some(TroveStatus::Zombie));
+    assert_eq!(stored.last_updated, 50);
+
+    store.close_troves(&[U256::from(2), U256::from(4)], 60).await.unwrap();
+    let closed = store.get_trove_by_id("2").await.unwrap().unwrap();
+    assert_eq!(TroveStatus::parse(&closed.status), Some(TroveStatus::ClosedByLiquidation));
+    assert_eq!(closed.last_updated, 60);
+    // Only open troves are closed
+    let owner_closed = store.get_trove_by_id("4").await.unwrap().unwrap();
+    assert_eq!(TroveStatus::parse(&owner_closed.status), Some(TroveStatus::ClosedByOwner));
+    assert_eq!(store.get_open_troves().await.unwrap().len(), 2);
+    // Locally closed troves stay in the reconciler's sweep until the liquidation zeroes their debt
+    assert_eq!(ids(&store.get_reconcilable_troves_after("", 10).await.unwrap()), ["1", "2", "3"]);
+
+    // Events, replay-safe
+    let events = [
+        event(10, "0xaa", 0, "openTrove"),
+        event(20, "0xab", 3, "adjustTrove"),
+        event(30, "0xbb", 1, "liquidate"),
+    ];
+    for event in &events {
+        store.insert_trove_event(event).await.unwrap();
+    }
+    store.insert_trove_event(&events[0]).await.unwrap();
+
+    let timeline = store.get_trove_timeline("1").await.unwrap();
+    let operations: Vec<&str> = timeline.iter().map(|event| event.operation.as_str()).collect();
+    assert_eq!(operations, ["openTrove", "adjustTrove", "liquidate"]);
+    assert_eq!(store.get_trove_timeline_between("1", 15, 30).await.unwrap().len(), 2);
+    let last = store.get_last_trove_event("1").await.unwrap().unwrap();
+    assert_eq!(last.operation, "liquidate");
+    assert_eq!(store.get_troves_liquidated_in_tx("0xbb").await.unwrap(), ["1"]);
+
+    // Liquidations, replay-safe
+    store.insert_liquidation(&liquidation("0xbb", true)).await.unwrap();
+    store.insert_liquidation(&liquidation("0xbb", true)).await.unwrap();
+    store.insert_liquidation(&liquidation("0xbc", false)).await.unwrap();
+    assert_eq!(store.get_liquidations_by_tx("0xbb").await.unwrap().len(), 1);
+
+    let by_branch = store.get_liquidation_summary_by_branch().await.unwrap();
+    assert_eq!(by_branch.len(), 1);
+    assert_eq!((by_branch[0].liquidations, by_branch[0].ours), (2, 1));
+    assert_eq!(by_branch[0].our_gas_compensation_usd, 100.0);
+    assert_eq!(by_branch[0].our_net_profit_usd, 50.0);
+    let by_day = store.get_liquidation_summary_by_day().await.unwrap();
+    assert_eq!(by_day.len(), 1);
+    assert_eq!((by_day[0].period.as_str(), by_day[0].ours), ("2023-11-14", 1));
+
+    // Candidates keep their first sighting
+    store.record_liquidation_candidates(&[U256::from(1), U256::from(2)], 100).await.unwrap();
+    store.record_liquidation_candidates(&[U256::from(1)], 105).await.unwrap();
+    let candidate = store.get_liquidation_candidate("1").await.unwrap().unwrap();
+    assert_eq!((candidate.first_liquidatable_block, candidate.last_seen_block), (100, 105));
+    assert!(!candidate.attempted);
+
+    store.mark_candidates_attempted(&[U256::from(1)], 106, Some("0xcc")).await.unwrap();
+    store.mark_candidates_attempted(&[U256::from(1)], 107, None).await.unwrap();
+    let candidate = store.get_liquidation_candidate("1").await.unwrap().unwrap();
+    assert!(candidate.attempted);
+    assert_eq!(candidate.attempt_block, Some(107));
+    assert_eq!(candidate.attempt_tx.as_deref(), Some("0xcc"));
+
+    // Competitor attribution
+    store.insert_liquidated_trove(&liquidated("1", "0xbc", 110, Some(107))).await.unwrap();
+    store.insert_liquidated_trove(&liquidated("1", "0xbc", 110, Some(107))).await.unwrap();
+    store.insert_liquidated_trove(&liquidated("2", "0xbd", 104, None)).await.unwrap();
+    let lost = store.get_lost_liquidations(10).await.unwrap();
+    let lost: Vec<&str> = lost.iter().map(|trove| trove.trove_id.as_str()).collect();
+    assert_eq!(lost, ["1", "2"]);
+
+    let stats = store.get_competitor_stats().await.unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].sender, "0xother");
+    assert_eq!((stats[0].troves, stats[0].tracked, stats[0].we_attempted), (2, 1, 1));
+    assert_eq!(stats[0].avg_blocks_after_eligible, Some(3.0));
+    assert_eq!(stats[0].min_blocks_after_eligible, Some(3));
+    assert_eq!(stats[0].avg_gas_price_gwei, 2.0);
+
+    // Shadow decisions keep the block they were first made
+    for block in [100, 101] {
+        let record = ShadowLiquidationInsert {
+            trove_id: "2".to_string(),
+            block_number: block,
+            batch_size: 1,
+            estimated_gas: Some(250_000),
+            gas_price: "1000000000".to_string(),
+            expected_coll_compensation: (5 * E18 / 100).to_string(),
+            expected_eth_compensation: "0".to_string(),
+            simulated_ok: true,
+            error: None,
+        };
+        store.record_shadow_liquidation(&record).await.unwrap();
+    }
+    // Only the first liquidation at or after the shadow block is compared
+    store.insert_liquidated_trove(&liquidated("2", "0xbe", 90, None)).await.unwrap();
+    store.insert_liquidated_trove(&liquidated("2", "0xbf", 120, None)).await.unwrap();
+    let comparison = store.get_shadow_comparison(10).await.unwrap();
+    assert_eq!(comparison.len(), 1);
+    assert_eq!(comparison[0].shadow_block, 100);
+    assert_eq!(comparison[0].actual_block, Some(104));
+    assert_eq!(comparison[0].blocks_after_shadow, Some(4));
+}
+
+#[tokio::test]
+async fn sqlite_store() {
+    let path = std::env::temp_dir().join(format!("liquidator-{}-store.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = initialize_database(&format!("sqlite:{}", path.display())).await.unwrap();
+    exercise_store(store).await;
+}
+
+/// A fresh SQLite pool on a per-test temp file
+async fn sqlite_pool(name: &str) -> (sqlx::SqlitePool, String) {
+    let path = std::env::temp_dir().join(format!("liquidator-{}-{}.db", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    let url = format!("sqlite:{}?mode=rwc", path.display());
+    (sqlx::SqlitePool::connect(&url).await.unwrap(), url)
+}
+
+#[tokio::test]
+async fn sqlite_migrates_a_pre_versioning_database() {
+    let (pool, url) = sqlite_pool("baseline").await;
+
+    // The schema and rows written before migrations were versioned
+    let baseline = [
+        "CREATE TABLE last_block (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            block_number INTEGER NOT NULL
+        )",
+        "CREATE TABLE troves (
+            trove_id TEXT PRIMARY KEY,
+            collateral TEXT NOT NULL,
+            debt TEXT NOT NULL,
+            interest_rate TEXT NOT NULL,
+            icr TEXT NOT NULL,
+            icr_numeric REAL NOT NULL,
+            status TEXT NOT NULL,
+            last_updated INTEGER NOT NULL
+        )",
+        "INSERT INTO last_block (id, block_number) VALUES (1, 1234)",
+    ];
+    for statement in baseline {
+        sqlx::query(statement).execute(&pool).await.unwrap();
+    }
+    let (coll, debt, rate) = (U256::from(10 * E18), U256::from(20_000 * E18), E18 / 20);
+    let rows = [
+        ("1", coll, debt, "active"),
+        ("2", coll, debt, "closed"),
+        ("3", U256::ZERO, U256::ZERO, "closed"),
+    ];
+    for (id, coll, debt, status) in rows {
+        sqlx::query(
+            "INSERT INTO troves (trove_id, collateral, debt, interest_rate, icr, icr_numeric,
+                status, last_updated)
+             VALUES (?, ?, ?, ?, '150.00', 150.0, ?, 1200)",
+        )
+        .bind(id)
+        .bind(coll.to_string())
+        .bind(debt.to_string())
+        .bind(rate.to_string())
+        .bind(status)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(migrate(&pool).await.unwrap(), latest_version());
+    pool.close().await;
+
+    let store = SqliteStore::new(sqlx::SqlitePool::connect(&url).await.unwrap());
+    assert_eq!(store.get_last_block().await.unwrap(), 1234);
+
+    let active = store.get_trove_by_id("1").await.unwrap().unwrap();
+    assert_eq!((active.collateral.0, active.debt.0), (coll, debt));
+    assert_eq!(active.interest_rate.0, U256::from(rate));
+    assert_eq!(active.icr_key, Trove::icr_key(coll, debt));
+    assert_eq!(active.status, TroveStatus::Active.as_str());
+    assert_eq!(active.liquidation_price.0, U256::ZERO);
+
+    // Closed troves still carrying debt need checking against the chain
+    let unreconciled = store.get_trove_by_id("2").await.unwrap().unwrap();
+    assert_eq!(unreconciled.status, TroveStatus::ClosedByLiquidation.as_str());
+    let emptied = store.get_trove_by_id("3").await.unwrap().unwrap();
+    assert_eq!(emptied.status, TroveStatus::ClosedByOwner.as_str());
+    assert_eq!(emptied.icr_key.0, U256::MAX);
+}
+
+#[tokio::test]
+async fn sqlite_refuses_a_newer_schema() {
+    let (pool, _) = sqlite_pool("newer").await;
+    migrate(&pool).await.unwrap();
+
+    sqlx::query(
+        "INSERT INTO schema_version (version, description, applied_at) VALUES (?, 'future', 0)",
+    )
+    .bind(latest_version() + 1)
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert!(migrate(&pool).await.is_err());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_POSTGRES_URL pointing at a scratch PostgreSQL database"]
+async fn postgres_store() {
+    let url = std::env::var("TEST_POSTGRES_URL").expect("TEST_POSTGRES_URL is not set");
+
+    // Start from an empty schema
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    for table in TABLES {
+        sqlx::query(&format!("DROP TABLE IF EXISTS {} CASCADE", table))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    pool.close().await;
+
+    let store = initialize_database(&url).await.unwrap();
+    exercise_store(store).await;
+}
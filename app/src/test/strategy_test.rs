@@ -0,0 +1,255 @@
+//! Pure strategy arithmetic and state transitions, checked against hand-computed values
+
+use alloy::primitives::{I256, U256};
+
+use crate::{
+    db::store::{Trove, TroveStatus},
+    liquity::{
+        liquidation_recorder::{liquidation_profit, signed_diff},
+        liquity::operation,
+        liquity_exexcution::ETH_GAS_COMPENSATION,
+        liquity_strategy::LiquityStrategy,
+        recheck_scheduler::project_liquidation_time,
+        sorted_troves::cross_check,
+        redemption_strategy::{
+            BranchPortion, estimate_redemption, max_fee_percentage, redemption_split,
+        },
+    },
+};
+
+const E18: u128 = 1_000_000_000_000_000_000;
+
+fn e18(amount: u64) -> U256 {
+    U256::from(amount) * U256::from(E18)
+}
+
+#[test]
+fn trove_updated_status() {
+    let (coll, debt) = (e18(10), e18(5_000));
+
+    assert_eq!(LiquityStrategy::updated_status(coll, debt, None), TroveStatus::Active);
+    // Zombies stay zombies until a TroveOperation revives them
+    let zombie = Some(TroveStatus::Zombie);
+    assert_eq!(LiquityStrategy::updated_status(coll, debt, zombie), TroveStatus::Zombie);
+
+    // An emptied trove keeps a closed status already set, and defaults to closed by its owner
+    let zero = U256::ZERO;
+    let liquidated = Some(TroveStatus::ClosedByLiquidation);
+    assert_eq!(
+        LiquityStrategy::updated_status(zero, zero, liquidated),
+        TroveStatus::ClosedByLiquidation
+    );
+    let active = Some(TroveStatus::Active);
+    assert_eq!(LiquityStrategy::updated_status(zero, zero, active), TroveStatus::ClosedByOwner);
+}
+
+#[test]
+fn trove_operation_status() {
+    let status = LiquityStrategy::operation_status;
+    let active = Some(TroveStatus::Active);
+    let zombie = Some(TroveStatus::Zombie);
+
+    assert_eq!(status(operation::OPEN_TROVE, None, None), Some(TroveStatus::Active));
+    assert_eq!(status(operation::CLOSE_TROVE, None, active), Some(TroveStatus::ClosedByOwner));
+    assert_eq!(
+        status(operation::LIQUIDATE, Some(e18(5_000)), active),
+        Some(TroveStatus::ClosedByLiquidation)
+    );
+
+    // Redemptions turn troves left below MIN_DEBT (2,000 BOLD) into zombies
+    assert_eq!(status(operation::REDEEM_COLLATERAL, Some(e18(1_999)), active), zombie);
+    assert_eq!(status(operation::REDEEM_COLLATERAL, Some(U256::ZERO), active), zombie);
+    assert_eq!(status(operation::REDEEM_COLLATERAL, Some(e18(2_000)), active), None);
+
+    // Adjustments revive a zombie only once its debt is back at MIN_DEBT
+    assert_eq!(status(operation::ADJUST_TROVE, Some(e18(2_000)), zombie), active);
+    assert_eq!(status(operation::APPLY_PENDING_DEBT, Some(e18(2_500)), zombie), active);
+    assert_eq!(status(operation::ADJUST_TROVE, Some(e18(1_000)), zombie), None);
+    assert_eq!(status(operation::ADJUST_TROVE, Some(e18(1_000)), active), None);
+
+    // Unknown troves and rate changes leave the stored status alone
+    assert_eq!(status(operation::REDEEM_COLLATERAL, None, None), None);
+    assert_eq!(status(operation::ADJUST_TROVE_INTEREST_RATE, Some(e18(1)), zombie), None);
+}
+
+#[test]
+fn liquidation_profit_prices_weth_compensation_with_the_native_price() {
+    // 0.05 coll at 2,000 plus 0.0375 WETH at 3,000, against 0.0003 ETH of gas
+    let profit = liquidation_profit(
+        U256::from(5 * E18 / 100),
+        U256::from(ETH_GAS_COMPENSATION),
+        U256::from(3 * E18 / 10_000),
+        e18(2_000),
+        e18(3_000),
+    );
+    assert_eq!(profit.gas_compensation_usd, U256::from(2_125 * E18 / 10));
+    // 212.5 - 0.9
+    assert_eq!(profit.net_profit_usd, I256::from_raw(U256::from(2_116 * E18 / 10)));
+    // 0.05 + 0.05625 coll received, 0.00045 coll of gas
+    assert_eq!(profit.net_profit_coll, I256::from_raw(U256::from(1_058 * E18 / 10_000)));
+}
+
+#[test]
+fn liquidation_profit_goes_negative_when_gas_exceeds_compensation() {
+    let profit = liquidation_profit(U256::ZERO, U256::ZERO, e18(1), e18(2_000), e18(2_000));
+    assert_eq!(profit.gas_compensation_usd, U256::ZERO);
+    assert_eq!(profit.net_profit_usd, -I256::from_raw(e18(2_000)));
+    assert_eq!(profit.net_profit_coll, -I256::from_raw(e18(1)));
+
+    // No price, no collateral-denominated result
+    let profit = liquidation_profit(e18(1), U256::ZERO, U256::ZERO, U256::ZERO, e18(2_000));
+    assert_eq!(profit.net_profit_coll, I256::ZERO);
+}
+
+#[test]
+fn liquidation_price_edge_cases() {
+    let rate = U256::from(5 * E18 / 100);
+    let mcr = U256::from(11 * E18 / 10);
+
+    assert_eq!(
+        LiquityStrategy::calculate_liquidation_price(U256::ZERO, e18(10), rate, mcr, 0),
+        U256::ZERO
+    );
+    assert_eq!(
+        LiquityStrategy::calculate_liquidation_price(e18(1_000), U256::ZERO, rate, mcr, 0),
+        U256::MAX
+    );
+    // 15,000 debt against 10 coll at a 110% MCR
+    assert_eq!(
+        LiquityStrategy::calculate_liquidation_price(e18(15_000), e18(10), rate, mcr, 0),
+        e18(1_650)
+    );
+    // A year at 5% on 1,000 debt against 1 coll
+    assert_eq!(
+        LiquityStrategy::calculate_liquidation_price(e18(1_000), e18(1), rate, mcr, 31_536_000),
+        e18(1_155)
+    );
+}
+
+#[test]
+fn liquidation_price_rounds_up_to_the_liquidation_boundary() {
+    let rate = U256::from(5 * E18 / 100);
+    let mcr = U256::from(11 * E18 / 10);
+    let (debt, coll) = (e18(1_000), e18(3));
+
+    let liquidation_price =
+        LiquityStrategy::calculate_liquidation_price(debt, coll, rate, mcr, 0);
+    assert_eq!(liquidation_price, U256::from(366_666_666_666_666_666_667u128));
+
+    // Liquidatable one wei below the liquidation price, safe at it
+    let icr_at = |price| LiquityStrategy::calculate_full_icr(debt, coll, rate, 0, 0, price);
+    assert!(icr_at(liquidation_price - U256::from(1)) < mcr);
+    assert!(icr_at(liquidation_price) >= mcr);
+}
+
+#[test]
+fn projected_liquidation_time_edge_cases() {
+    let rate = U256::from(5 * E18 / 100);
+    let mcr = U256::from(11 * E18 / 10);
+    let at = 1_700_000_000;
+
+    assert_eq!(project_liquidation_time(U256::ZERO, e18(1), rate, mcr, e18(2_000), at), None);
+    // 10 coll at 1,000 carries at most 9,090.90 debt
+    assert_eq!(project_liquidation_time(e18(10_000), e18(10), rate, mcr, e18(1_000), at), Some(at));
+    // Exactly at MCR is not liquidatable, and without interest never becomes so
+    let at_mcr = |rate| project_liquidation_time(e18(10_000), e18(11), rate, mcr, e18(1_000), at);
+    assert_eq!(at_mcr(U256::ZERO), None);
+    assert!(at_mcr(rate) > Some(at));
+}
+
+#[test]
+fn projected_liquidation_time_is_the_first_second_below_mcr() {
+    let rate = U256::from(5 * E18 / 100);
+    let mcr = U256::from(11 * E18 / 10);
+    let (debt, coll, price) = (e18(1_000), e18(1), e18(1_155));
+    let at = 1_700_000_000;
+
+    // 1,050 debt is the most 1 coll at 1,155 carries: a year at 5%, and one more second
+    let crossing = project_liquidation_time(debt, coll, rate, mcr, price, at).unwrap();
+    assert_eq!(crossing, at + 31_536_000 + 1);
+
+    let icr_at = |now| LiquityStrategy::calculate_full_icr(debt, coll, rate, now, at, price);
+    assert!(icr_at(crossing) < mcr);
+    assert!(icr_at(crossing - 1) >= mcr);
+}
+
+fn branch(unbacked: u64, price: u64, is_ours: bool) -> BranchPortion {
+    BranchPortion { unbacked: e18(unbacked), price: e18(price), is_ours }
+}
+
+#[test]
+fn redemption_splits_by_unbacked_debt() {
+    let branches = [branch(3_000, 2_000, true), branch(1_000, 100_000, false)];
+    assert_eq!(redemption_split(e18(10_000), &branches), [e18(7_500), e18(2_500)]);
+
+    // Unredeemable branches carry no unbacked debt and receive nothing
+    let branches = [branch(0, 2_000, true), branch(0, 100_000, false)];
+    assert_eq!(redemption_split(e18(10_000), &branches), [U256::ZERO, U256::ZERO]);
+}
+
+#[test]
+fn redemption_estimate_nets_the_fee_on_every_branch() {
+    // 10,000 BOLD at a 1% fee: 7,500 on our 2,000 branch and 2,500 on a 100,000 branch
+    let branches = [branch(3_000, 2_000, true), branch(1_000, 100_000, false)];
+    let estimate = estimate_redemption(e18(10_000), U256::from(E18 / 100), &branches, e18(2_100));
+
+    assert_eq!(estimate.branch_bold, e18(7_500));
+    // 7,425 BOLD after the fee, at 2,000
+    assert_eq!(estimate.collateral_received, U256::from(37_125 * E18 / 10_000));
+    // 3.7125 coll at the 2,100 oracle price, plus 2,475 BOLD of collateral at its redemption price
+    assert_eq!(estimate.collateral_value_usd, U256::from(7_796_250 * E18 / 1_000) + e18(2_475));
+
+    let cost = e18(10_000) * U256::from(99) / U256::from(100);
+    let profit = signed_diff(estimate.collateral_value_usd, cost);
+    assert_eq!(profit, I256::from_raw(U256::from(371_250 * E18 / 1_000)));
+}
+
+#[test]
+fn redemption_max_fee_leaves_headroom() {
+    let fee_rate = U256::from(5 * E18 / 1_000);
+    // 0.5% quoted plus 25 bps
+    assert_eq!(max_fee_percentage(fee_rate, 25), U256::from(75 * E18 / 10_000));
+    assert_eq!(max_fee_percentage(fee_rate, 0), fee_rate);
+    // Never above 100%
+    assert_eq!(max_fee_percentage(U256::from(E18), 25), U256::from(E18));
+}
+
+fn listed_trove(id: u64, rate_pct: u64, status: TroveStatus) -> Trove {
+    let (coll, debt) = (e18(10), e18(5_000));
+    Trove {
+        trove_id: id.to_string(),
+        collateral: coll.into(),
+        debt: debt.into(),
+        icr_key: Trove::icr_key(coll, debt),
+        interest_rate: (e18(rate_pct) / U256::from(100)).into(),
+        status: status.as_str().to_string(),
+        last_updated: 0,
+        last_updated_at: 0,
+        liquidation_price: U256::ZERO.into(),
+        liquidation_price_until: 0,
+    }
+}
+
+#[test]
+fn sorted_troves_cross_check_reports_membership_and_rate_order() {
+    let open = [
+        listed_trove(1, 3, TroveStatus::Active),
+        listed_trove(2, 5, TroveStatus::Active),
+        listed_trove(3, 4, TroveStatus::Active),
+        listed_trove(4, 6, TroveStatus::Active),
+        // Zombies are unlisted on-chain
+        listed_trove(5, 1, TroveStatus::Zombie),
+    ];
+    // Walked from the last node: lowest rate first, with 3 listed after the higher-rate 2
+    let listed = [1, 2, 3, 9].map(U256::from);
+
+    let check = cross_check(&listed, &open);
+    assert_eq!((check.listed, check.active), (4, 4));
+    assert_eq!(check.missing_from_list, ["4"]);
+    assert_eq!(check.missing_from_db, ["9"]);
+    assert_eq!(check.rate_inversions, 1);
+
+    let check = cross_check(&[1, 3, 2, 4].map(U256::from), &open);
+    assert!(check.missing_from_list.is_empty() && check.missing_from_db.is_empty());
+    assert_eq!(check.rate_inversions, 0);
+}
@@ -4,6 +4,7 @@ pragma solidity ^0.8.0;
 
 import './IPriceFeed.sol';
 import "./TroveManager.sol";
+import "./ISortedTroves.sol";
 interface AddressesRegistry {
     
 
@@ -17,6 +18,8 @@ interface AddressesRegistry {
 
     function troveManager() external view returns (TroveManager);
 
+    function sortedTroves() external view returns (ISortedTroves);
+
     function priceFeed() external view returns (IPriceFeed);
     function gasPoolAddress() external view returns (address);
 }
\ No newline at end of file
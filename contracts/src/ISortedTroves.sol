@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+interface ISortedTroves {
+    function contains(uint256 _id) external view returns (bool);
+
+    function isEmpty() external view returns (bool);
+
+    function getSize() external view returns (uint256);
+
+    function getFirst() external view returns (uint256);
+
+    function getLast() external view returns (uint256);
+
+    function getNext(uint256 _id) external view returns (uint256);
+
+    function getPrev(uint256 _id) external view returns (uint256);
+}
@@ -16,6 +16,10 @@ interface TroveManager{
 
     function shutdownTime() external view returns (uint256);
 
+    function getEntireBranchColl() external view returns (uint256);
+
+    function getEntireBranchDebt() external view returns (uint256);
+
     //function boldToken() external view returns (IBoldToken);
 
     function Troves(uint256 _id)
@@ -42,6 +46,21 @@ interface TroveManager{
 
     function getCurrentICR(uint256 _troveId, uint256 _price) external view returns (uint256);
 
+    struct LatestTroveData {
+        uint256 entireDebt;
+        uint256 entireColl;
+        uint256 redistBoldDebtGain;
+        uint256 redistCollGain;
+        uint256 accruedInterest;
+        uint256 recordedDebt;
+        uint256 annualInterestRate;
+        uint256 weightedRecordedDebt;
+        uint256 accruedBatchManagementFee;
+        uint256 lastInterestRateAdjTime;
+    }
+
+    function getLatestTroveData(uint256 _troveId) external view returns (LatestTroveData memory);
+
     function lastZombieTroveId() external view returns (uint256);
 
     function batchLiquidateTroves(uint256[] calldata _troveArray) external;
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.24;
+
+/// Minimal stand-ins for a Liquity v2 branch, used by the bot's anvil integration tests.
+/// They emit the same events as the real contracts but skip the stability pool, redistribution
+/// and interest accrual.
+
+contract MockPriceFeed {
+    int256 public latestAnswer;
+    uint256 public latestTimestamp;
+
+    /// `answer` has 8 decimals, like a Chainlink feed
+    constructor(int256 answer) {
+        setPrice(answer);
+    }
+
+    function setPrice(int256 answer) public {
+        latestAnswer = answer;
+        latestTimestamp = block.timestamp;
+    }
+
+    function lastGoodPrice() public view returns (uint256) {
+        return uint256(latestAnswer) * 1e10;
+    }
+
+    function fetchPrice() external view returns (uint256, bool) {
+        return (lastGoodPrice(), false);
+    }
+
+    function fetchRedemptionPrice() external view returns (uint256, bool) {
+        return (lastGoodPrice(), false);
+    }
+}
+
+contract MockTroveManager {
+    enum Status {
+        nonExistent,
+        active,
+        closedByOwner,
+        closedByLiquidation,
+        zombie
+    }
+
+    enum Operation {
+        openTrove,
+        closeTrove,
+        adjustTrove,
+        adjustTroveInterestRate,
+        applyPendingDebt,
+        liquidate,
+        redeemCollateral,
+        openTroveAndJoinBatch,
+        setInterestBatchManager,
+        removeFromBatch
+    }
+
+    struct Trove {
+        uint256 debt;
+        uint256 coll;
+        Status status;
+        uint64 lastDebtUpdateTime;
+        uint256 annualInterestRate;
+    }
+
+    struct LatestTroveData {
+        uint256 entireDebt;
+        uint256 entireColl;
+        uint256 redistBoldDebtGain;
+        uint256 redistCollGain;
+        uint256 accruedInterest;
+        uint256 recordedDebt;
+        uint256 annualInterestRate;
+        uint256 weightedRecordedDebt;
+        uint256 accruedBatchManagementFee;
+        uint256 lastInterestRateAdjTime;
+    }
+
+    event Liquidation(
+        uint256 _debtOffsetBySP,
+        uint256 _debtRedistributed,
+        uint256 _boldGasCompensation,
+        uint256 _collGasCompensation,
+        uint256 _collSentToSP,
+        uint256 _collRedistributed,
+        uint256 _collSurplus,
+        uint256 _L_ETH,
+        uint256 _L_boldDebt,
+        uint256 _price
+    );
+
+    event TroveUpdated(
+        uint256 indexed _troveId,
+        uint256 _debt,
+        uint256 _coll,
+        uint256 _stake,
+        uint256 _annualInterestRate,
+        uint256 _snapshotOfTotalCollRedist,
+        uint256 _snapshotOfTotalDebtRedist
+    );
+
+    event TroveOperation(
+        uint256 indexed _troveId,
+        Operation _operation,
+        uint256 _annualInterestRate,
+        uint256 _debtIncreaseFromRedist,
+        uint256 _debtIncreaseFromUpfrontFee,
+        int256 _debtChangeFromOperation,
+        uint256 _collIncreaseFromRedist,
+        int256 _collChangeFromOperation
+    );
+
+    uint256 constant COLL_GAS_COMPENSATION_DIVISOR = 200;
+    uint256 constant COLL_GAS_COMPENSATION_CAP = 2e18;
+
+    MockPriceFeed public immutable priceFeed;
+    uint256 public immutable MCR;
+
+    mapping(uint256 => Trove) internal troves;
+    uint256 public totalColl;
+    uint256 public totalDebt;
+
+    constructor(MockPriceFeed _priceFeed, uint256 _mcr) {
+        priceFeed = _priceFeed;
+        MCR = _mcr;
+    }
+
+    function openTrove(uint256 _troveId, uint256 _coll, uint256 _debt, uint256 _annualInterestRate)
+        external
+    {
+        require(troves[_troveId].status == Status.nonExistent, "TROVE_EXISTS");
+        troves[_troveId] = Trove({
+            debt: _debt,
+            coll: _coll,
+            status: Status.active,
+            lastDebtUpdateTime: uint64(block.timestamp),
+            annualInterestRate: _annualInterestRate
+        });
+        totalColl += _coll;
+        totalDebt += _debt;
+
+        emit TroveUpdated(_troveId, _debt, _coll, _coll, _annualInterestRate, 0, 0);
+        emit TroveOperation(
+            _troveId, Operation.openTrove, _annualInterestRate, 0, 0, int256(_debt), 0, int256(_coll)
+        );
+    }
+
+    function Troves(uint256 _id)
+        external
+        view
+        returns (
+            uint256 debt,
+            uint256 coll,
+            uint256 stake,
+            Status status,
+            uint64 arrayIndex,
+            uint64 lastDebtUpdateTime,
+            uint64 lastInterestRateAdjTime,
+            uint256 annualInterestRate,
+            address interestBatchManager,
+            uint256 batchDebtShares
+        )
+    {
+        Trove memory trove = troves[_id];
+        return (
+            trove.debt,
+            trove.coll,
+            trove.coll,
+            trove.status,
+            0,
+            trove.lastDebtUpdateTime,
+            trove.lastDebtUpdateTime,
+            trove.annualInterestRate,
+            address(0),
+            0
+        );
+    }
+
+    function getTroveStatus(uint256 _troveId) external view returns (Status) {
+        return troves[_troveId].status;
+    }
+
+    function getCurrentICR(uint256 _troveId, uint256 _price) public view returns (uint256) {
+        Trove memory trove = troves[_troveId];
+        if (trove.debt == 0) return type(uint256).max;
+        return trove.coll * _price / trove.debt;
+    }
+
+    function getLatestTroveData(uint256 _troveId) external view returns (LatestTroveData memory data) {
+        Trove memory trove = troves[_troveId];
+        data.entireDebt = trove.debt;
+        data.entireColl = trove.coll;
+        data.recordedDebt = trove.debt;
+        data.annualInterestRate = trove.annualInterestRate;
+        data.weightedRecordedDebt = trove.debt * trove.annualInterestRate;
+        data.lastInterestRateAdjTime = trove.lastDebtUpdateTime;
+    }
+
+    function getEntireBranchColl() external view returns (uint256) {
+        return totalColl;
+    }
+
+    function getEntireBranchDebt() external view returns (uint256) {
+        return totalDebt;
+    }
+
+    function shutdownTime() external pure returns (uint256) {
+        return 0;
+    }
+
+    /// Liquidates every listed trove under MCR at the feed price; the whole debt counts as
+    /// offset by the stability pool
+    function batchLiquidateTroves(uint256[] calldata _troveArray) external {
+        uint256 price = priceFeed.lastGoodPrice();
+        uint256 debtOffset;
+        uint256 collGasCompensation;
+        uint256 collSentToSP;
+
+        for (uint256 i = 0; i < _troveArray.length; i++) {
+            uint256 troveId = _troveArray[i];
+            Trove storage trove = troves[troveId];
+            if (trove.status != Status.active || getCurrentICR(troveId, price) >= MCR) continue;
+
+            uint256 compensation = trove.coll / COLL_GAS_COMPENSATION_DIVISOR;
+            if (compensation > COLL_GAS_COMPENSATION_CAP) compensation = COLL_GAS_COMPENSATION_CAP;
+            debtOffset += trove.debt;
+            collGasCompensation += compensation;
+            collSentToSP += trove.coll - compensation;
+
+            emit TroveUpdated(troveId, 0, 0, 0, 0, 0, 0);
+            emit TroveOperation(
+                troveId,
+                Operation.liquidate,
+                0,
+                0,
+                0,
+                -int256(trove.debt),
+                0,
+                -int256(trove.coll)
+            );
+
+            totalColl -= trove.coll;
+            totalDebt -= trove.debt;
+            trove.debt = 0;
+            trove.coll = 0;
+            trove.status = Status.closedByLiquidation;
+        }
+
+        require(debtOffset > 0, "NOTHING_TO_LIQUIDATE");
+        emit Liquidation(debtOffset, 0, 0, collGasCompensation, collSentToSP, 0, 0, 0, 0, price);
+    }
+}
+
+contract MockAddressesRegistry {
+    uint256 public immutable MCR;
+    uint256 public immutable SCR;
+    MockTroveManager public immutable troveManager;
+    MockPriceFeed public immutable priceFeed;
+    address public immutable sortedTroves;
+
+    constructor(MockTroveManager _troveManager, MockPriceFeed _priceFeed, uint256 _mcr, uint256 _scr) {
+        troveManager = _troveManager;
+        priceFeed = _priceFeed;
+        MCR = _mcr;
+        SCR = _scr;
+        sortedTroves = address(0);
+    }
+}